@@ -0,0 +1,746 @@
+/*
+ * Interface between the kernel and user programs. The kernel and the
+ * userlib crate both use these definitions, so they can´t disagree.
+ * Numbers are only appended, existing ones never change.
+ *
+ * Calling convention:
+ * The syscall instruction is executed with the number in rdi and the
+ * arguments in rsi, rdx, r14, r8, r9 and r15. The result is returned
+ * in rax. rcx, rdx, rsi, r8, r9 and r11 aren´t preserved.
+ *
+ * Paths:
+ * Files are found in one namespace, which filesystems are mounted
+ * into. Paths are UTF-8 and relative ones start at the working
+ * directory of the process. "." and ".." are resolved before
+ * mount points are looked up, so ".." of a mount point leaves it.
+ */
+#![no_std]
+
+pub mod syscall {
+	/*
+	 * Exits the current process with the exit code (1).
+	 */
+	pub const EXIT: u64 = 0;
+	/*
+	 * Powers off the PC.
+	 */
+	pub const SHUTDOWN: u64 = 1;
+	/*
+	 * Reboots the PC.
+	 */
+	pub const REBOOT: u64 = 2;
+	/*
+	 * Prints the UTF-8 string at the pointer (1) with the length (2).
+	 * Returns 0, BadAddress or InvalidArgument for invalid UTF-8.
+	 */
+	pub const PRINT: u64 = 3;
+	/*
+	 * Executes the ELF at the path (1: pointer, 2: length). The
+	 * ExecArguments at the pointer (3), if it isn´t zero, are passed on
+	 * the stack of the program (see process). The file descriptors in
+	 * the array (4) with the amount of entries (5, at most STDIO_AMOUNT)
+	 * become stdin, stdout and stderr of the program, NO_FILE leaves one
+	 * closed. The program starts in the working directory of the caller.
+	 * Returns the pid of the program, the error of reading the file,
+	 * InvalidExecutable, BadFileDescriptor, BadAddress, InvalidArgument
+	 * or ArgumentListTooLong.
+	 */
+	pub const EXEC: u64 = 4;
+	/*
+	 * Claims the graphic manager flag for the current process.
+	 * Returns 0.
+	 */
+	pub const CLAIM_GRAPHIC_MANAGER: u64 = 5;
+	/*
+	 * Creates a layer with the z coordinate (1). Returns the id of the
+	 * layer or NotPermitted, if the process isn´t the graphic manager.
+	 */
+	pub const ADD_LAYER: u64 = 6;
+	/*
+	 * Draws a rectangle at the position (2, 3) with the size (4, 5) and
+	 * the color (6: 0xbbggrr) in the layer (1). Returns 0, NotFound for
+	 * unknown layers or InvalidArgument for rectangles, which aren´t
+	 * inside the layer.
+	 */
+	pub const DRAW_RECT: u64 = 7;
+	/*
+	 * Reads the amount of bytes (3) at the offset (4) of the file at the
+	 * path (1: pointer, 2: length). Returns the address of a new buffer
+	 * with the content, which is freed like an allocation with the length
+	 * of the content, NotFound or InvalidArgument.
+	 */
+	pub const READ_FILE: u64 = 8;
+	/*
+	 * Allocates the amount of bytes (1) like MMAP with PROT_WRITE at an
+	 * address picked by the kernel. Returns the address, InvalidArgument
+	 * or OutOfMemory.
+	 */
+	pub const ALLOCATE: u64 = 9;
+	/*
+	 * Frees the allocation at the address (1) with the size (2) like
+	 * MUNMAP. Returns 0 or InvalidArgument.
+	 */
+	pub const FREE: u64 = 10;
+	/*
+	 * Waits until the child with the pid (1, ANY_CHILD for any child)
+	 * exits and returns its pid. If the pointer (2) isn´t zero, the exit
+	 * code is written to it. Returns NoChild, when there´s no such child,
+	 * or BadAddress.
+	 */
+	pub const WAIT: u64 = 11;
+	/*
+	 * Creates a child process with a copy on write copy of the address
+	 * space. Returns the pid of the child to the parent, 0 to the child
+	 * or OutOfMemory.
+	 */
+	pub const FORK: u64 = 12;
+	/*
+	 * Restricts the threads of the process to the cores in the mask (1).
+	 * Bit n stands for the core with the LAPIC id n. Returns 0 or
+	 * InvalidArgument, if none of the cores is running.
+	 */
+	pub const SET_AFFINITY: u64 = 13;
+	/*
+	 * Returns the mask of the running cores, which the process may use.
+	 */
+	pub const AFFINITY: u64 = 14;
+	/*
+	 * Writes a ProcessRecord for every process to the buffer (1) with
+	 * space for the amount of records (2). Returns the amount of
+	 * processes, which may exceed the space, or BadAddress.
+	 */
+	pub const PROCESS_LIST: u64 = 15;
+	/*
+	 * Sets the priority class (1, see process) of the current thread.
+	 * Returns 0, InvalidArgument for unknown classes or NotPermitted,
+	 * when an unprivileged process raises a thread to realtime.
+	 */
+	pub const SET_PRIORITY: u64 = 16;
+	/*
+	 * Returns the priority class of the current thread.
+	 */
+	pub const PRIORITY: u64 = 17;
+	/*
+	 * Starts a thread at the entry (1) with the stack top (2). The
+	 * argument (3) is passed in rdi. Returns the tid or NoProcess,
+	 * when the process exits.
+	 */
+	pub const CREATE_THREAD: u64 = 18;
+	/*
+	 * Exits the current thread with the exit code (1). The process
+	 * exits with its last thread and the same exit code.
+	 */
+	pub const EXIT_THREAD: u64 = 19;
+	/*
+	 * Waits until the thread with the tid (1) exits. If the pointer (2)
+	 * isn´t zero, the exit code is written to it. Returns 0, NoProcess,
+	 * when the process has no such thread, or BadAddress.
+	 */
+	pub const JOIN_THREAD: u64 = 20;
+	/*
+	 * Sends the signal (2) to the process with the pid (1). Signal 0 only
+	 * checks, whether the process exists. Processes may signal themselves
+	 * and their children, privileged ones every process except kernel
+	 * tasks. Returns 0, NoProcess, NotPermitted or InvalidArgument for
+	 * unknown signals.
+	 */
+	pub const KILL: u64 = 21;
+	/*
+	 * Sets the handler (2) of the signal (1). SIG_DFL restores the default
+	 * action and SIG_IGN ignores the signal. The handler gets the signal
+	 * in rdi and returns to the restorer (3), which has to call SIGRETURN
+	 * without changing the stack pointer. Returns the previous handler
+	 * or InvalidArgument.
+	 */
+	pub const SIGACTION: u64 = 22;
+	/*
+	 * Returns from a signal handler to the interrupted code.
+	 */
+	pub const SIGRETURN: u64 = 23;
+	/*
+	 * Blocks the current thread for at least the nanoseconds (1).
+	 * Returns 0.
+	 */
+	pub const SLEEP: u64 = 24;
+	/*
+	 * Returns the time of the clock (1, see clock) in nanoseconds. If the
+	 * pointer (2) isn´t zero, a Timespec is written to it. Returns
+	 * InvalidArgument for unknown clocks or BadAddress.
+	 */
+	pub const CLOCK_GETTIME: u64 = 25;
+	/*
+	 * Opens the file at the path (1: pointer, 2: length) with the flags
+	 * (3, see file). Returns the file descriptor, NotFound, IsDirectory,
+	 * InvalidArgument for unknown flags or invalid names,
+	 * ReadOnlyFilesystem, NoSpace or TooManyFiles.
+	 */
+	pub const OPEN: u64 = 26;
+	/*
+	 * Reads up to the amount of bytes (3) at the offset of the file
+	 * descriptor (1) to the buffer (2) and advances the offset.
+	 * Returns the amount of bytes read, 0 at the end of the file,
+	 * BadFileDescriptor or BadAddress.
+	 */
+	pub const READ: u64 = 27;
+	/*
+	 * Writes the amount of bytes (3) in the buffer (2) at the offset of
+	 * the file descriptor (1) and advances the offset. Returns the amount
+	 * of bytes written, BadFileDescriptor, BadAddress, ReadOnlyFilesystem
+	 * or NoSpace.
+	 */
+	pub const WRITE: u64 = 28;
+	/*
+	 * Moves the offset of the file descriptor (1) to the signed offset (2)
+	 * relative to the origin (3: SEEK_SET, SEEK_CUR or SEEK_END). Returns
+	 * the new offset, BadFileDescriptor, InvalidArgument or IllegalSeek
+	 * for pipes.
+	 */
+	pub const LSEEK: u64 = 29;
+	/*
+	 * Closes the file descriptor (1). Returns 0 or BadFileDescriptor.
+	 */
+	pub const CLOSE: u64 = 30;
+	/*
+	 * Writes a FileStat of the file descriptor (1) to the pointer (2).
+	 * Returns 0, BadFileDescriptor or BadAddress.
+	 */
+	pub const FSTAT: u64 = 31;
+	/*
+	 * Creates a pipe and writes the descriptors of its read and its write
+	 * end to the array (1) of two entries. Reads block, while the pipe is
+	 * empty, and return 0, when all write ends are closed. Writes block,
+	 * while it´s full. Writing without read ends raises SIGPIPE and
+	 * fails with BrokenPipe. Returns 0, TooManyFiles or BadAddress.
+	 */
+	pub const PIPE: u64 = 32;
+	/*
+	 * Creates zeroed shared memory with the size (1), which is rounded up
+	 * to whole pages, and opens it for reading and writing. If the length
+	 * (3) isn´t zero, other processes find it by the name at the pointer
+	 * (2) with SHM_OPEN. The memory exists, until every descriptor and
+	 * mapping of it is closed. Returns the file descriptor, AlreadyExists,
+	 * InvalidArgument for sizes of zero or above MAX_SHARED_MEMORY_SIZE,
+	 * OutOfMemory, TooManyFiles or BadAddress.
+	 */
+	pub const SHM_CREATE: u64 = 33;
+	/*
+	 * Opens the shared memory with the name (1: pointer, 2: length) with
+	 * the flags (3, see file). Returns the file descriptor, NotFound,
+	 * InvalidArgument, TooManyFiles or BadAddress.
+	 */
+	pub const SHM_OPEN: u64 = 34;
+	/*
+	 * Maps the shared memory of the file descriptor (1) at the page
+	 * aligned address (2) or at an address picked by the kernel, if it´s
+	 * zero. The flags (3, see memory) choose, whether it´s writable.
+	 * Forked children keep the mapping. Returns the address,
+	 * BadFileDescriptor, NoDevice for other files, NotPermitted for
+	 * writable mappings of descriptors opened without OPEN_WRITE,
+	 * InvalidArgument, when the address range isn´t free, or OutOfMemory.
+	 */
+	pub const SHM_MAP: u64 = 35;
+	/*
+	 * Unmaps the shared memory mapped at the address (1). Returns 0 or
+	 * InvalidArgument, if no shared memory is mapped there.
+	 */
+	pub const SHM_UNMAP: u64 = 36;
+	/*
+	 * Creates a port with the name (1: pointer, 2: length), which the
+	 * current process receives messages of. It´s destroyed with the
+	 * process. Returns the port id, AlreadyExists, InvalidArgument for
+	 * empty names or BadAddress.
+	 */
+	pub const PORT_CREATE: u64 = 37;
+	/*
+	 * Returns the id of the port with the name (1: pointer, 2: length),
+	 * NotFound or BadAddress.
+	 */
+	pub const PORT_LOOKUP: u64 = 38;
+	/*
+	 * Destroys the port (1). Waiting senders fail with BrokenPipe.
+	 * Returns 0, NotFound or NotPermitted, if the current process
+	 * doesn´t own the port.
+	 */
+	pub const PORT_DESTROY: u64 = 39;
+	/*
+	 * Queues the message (2: pointer, 3: length, at most MAX_MESSAGE_SIZE)
+	 * at the port (1) without waiting for a reply. Blocks, while the
+	 * queue is full. Returns 0, NotFound, InvalidArgument, BrokenPipe,
+	 * when the port is destroyed meanwhile, or BadAddress.
+	 */
+	pub const PORT_SEND: u64 = 40;
+	/*
+	 * Sends the message (2: pointer, 3: length) to the port (1) like
+	 * PORT_SEND and blocks, until the owner replies. The reply is copied
+	 * to the buffer (4) with the capacity (5) and truncated, if it
+	 * doesn´t fit. Returns the length of the reply or the errors of
+	 * PORT_SEND.
+	 */
+	pub const PORT_CALL: u64 = 41;
+	/*
+	 * Blocks, until a message is queued at the port (1), and copies it to
+	 * the buffer (2) with the capacity (3). Longer messages are truncated.
+	 * If the pointer (4) isn´t zero, a MessageInfo is written to it.
+	 * Returns the length of the copied message, NotFound, NotPermitted, if
+	 * the current process doesn´t own the port, or BadAddress.
+	 */
+	pub const PORT_RECEIVE: u64 = 42;
+	/*
+	 * Replies with the message (3: pointer, 4: length) to the call with
+	 * the id (2) received from the port (1). Returns 0, NotFound, if no
+	 * such call waits for a reply, NotPermitted, InvalidArgument or
+	 * BadAddress.
+	 */
+	pub const PORT_REPLY: u64 = 43;
+	/*
+	 * Maps zeroed memory with the size (2), which is rounded up to whole
+	 * pages, and the protection (3, see memory) at the page aligned
+	 * address (1) or at an address picked by the kernel, if it´s zero.
+	 * The memory belongs to the process, forked children get a copy on
+	 * write copy. Returns the address, InvalidArgument, when the size is
+	 * zero or the address range isn´t free, or OutOfMemory.
+	 */
+	pub const MMAP: u64 = 44;
+	/*
+	 * Unmaps the mapping at the address (1) with the size (2). Only whole
+	 * mappings of the process are unmapped, shared memory is unmapped with
+	 * SHM_UNMAP. Returns 0 or InvalidArgument, if there´s no such mapping.
+	 */
+	pub const MUNMAP: u64 = 45;
+	/*
+	 * Changes the protection (3) of the whole mapping at the address (1)
+	 * with the size (2). Returns 0 or InvalidArgument, if there´s no such
+	 * mapping or the protection is unknown.
+	 */
+	pub const MPROTECT: u64 = 46;
+	/*
+	 * Creates a directory at the path (1: pointer, 2: length). Returns 0,
+	 * NotFound, NotDirectory, AlreadyExists, InvalidArgument for invalid
+	 * names, ReadOnlyFilesystem or NoSpace.
+	 */
+	pub const MKDIR: u64 = 47;
+	/*
+	 * Removes the file at the path (1: pointer, 2: length). Returns 0,
+	 * NotFound, IsDirectory or ReadOnlyFilesystem.
+	 */
+	pub const UNLINK: u64 = 48;
+	/*
+	 * Removes the empty directory at the path (1: pointer, 2: length).
+	 * Returns 0, NotFound, NotDirectory, DirectoryNotEmpty, Busy for
+	 * mount points or ReadOnlyFilesystem.
+	 */
+	pub const RMDIR: u64 = 49;
+	/*
+	 * Changes the size of the file of the descriptor (1) to the size (2).
+	 * Files grow with zeros. Returns 0, BadFileDescriptor, if it isn´t
+	 * open for writing, InvalidArgument for other than filesystem files,
+	 * ReadOnlyFilesystem or NoSpace.
+	 */
+	pub const FTRUNCATE: u64 = 50;
+	/*
+	 * Writes a FileStat of the file or directory at the path (1: pointer,
+	 * 2: length) to the pointer (3). Returns 0, NotFound, NotDirectory
+	 * or BadAddress.
+	 */
+	pub const STAT: u64 = 51;
+	/*
+	 * Writes DirectoryRecords of the directory at the path (1: pointer,
+	 * 2: length) to the array (3) with the capacity (4) in records. The
+	 * records start with the entry at the index (5), "." and ".." aren´t
+	 * listed. Returns the amount of records written, which is zero after
+	 * the last entry, NotFound, NotDirectory or BadAddress.
+	 */
+	pub const READDIR: u64 = 52;
+	/*
+	 * Changes the working directory of the process to the directory at
+	 * the path (1: pointer, 2: length). Forked children and executed
+	 * programs start in it. Returns 0, NotFound or NotDirectory.
+	 */
+	pub const CHDIR: u64 = 53;
+	/*
+	 * Writes the absolute path of the working directory to the buffer
+	 * (1) with the capacity (2) in bytes, if it fits. Returns the length
+	 * of the path or BadAddress.
+	 */
+	pub const GETCWD: u64 = 54;
+	/*
+	 * Mounts the filesystem of the disk (1) at the directory of the path
+	 * (2: pointer, 3: length). Returns 0, NoDevice for unknown disks or
+	 * filesystems, Busy, if the disk or the directory is mounted already,
+	 * NotFound, NotDirectory or NotPermitted for unprivileged processes.
+	 */
+	pub const MOUNT: u64 = 55;
+
+	pub const COUNT: usize = 56;
+}
+
+pub mod error {
+	/*
+	 * Errors of syscalls. Codes match the errno values of POSIX systems
+	 * on x86_64. A failing syscall returns the negated code, so errors
+	 * are the values from -MAX_CODE to -1 and never valid pointers or ids.
+	 */
+	#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+	#[repr(u64)]
+	pub enum Error {
+		NotPermitted = 1,
+		NotFound = 2,
+		NoProcess = 3,
+		InputOutput = 5,
+		ArgumentListTooLong = 7,
+		InvalidExecutable = 8,
+		NoChild = 10,
+		OutOfMemory = 12,
+		BadFileDescriptor = 9,
+		BadAddress = 14,
+		// The path is a mount point or the disk is mounted already.
+		Busy = 16,
+		AlreadyExists = 17,
+		NoDevice = 19,
+		NotDirectory = 20,
+		IsDirectory = 21,
+		InvalidArgument = 22,
+		TooManyFiles = 24,
+		NoSpace = 28,
+		IllegalSeek = 29,
+		ReadOnlyFilesystem = 30,
+		BrokenPipe = 32,
+		// Returned for unknown syscall numbers.
+		UnknownSyscall = 38,
+		DirectoryNotEmpty = 39
+	}
+
+	pub const MAX_CODE: u64 = 4095;
+
+	impl Error {
+		pub const fn code(self) -> u64 {
+			self as u64
+		}
+
+		pub const fn from_code(code: u64) -> Option<Error> {
+			Some(match code {
+				1 => Error::NotPermitted,
+				2 => Error::NotFound,
+				3 => Error::NoProcess,
+				5 => Error::InputOutput,
+				7 => Error::ArgumentListTooLong,
+				8 => Error::InvalidExecutable,
+				9 => Error::BadFileDescriptor,
+				10 => Error::NoChild,
+				12 => Error::OutOfMemory,
+				14 => Error::BadAddress,
+				16 => Error::Busy,
+				17 => Error::AlreadyExists,
+				19 => Error::NoDevice,
+				20 => Error::NotDirectory,
+				21 => Error::IsDirectory,
+				22 => Error::InvalidArgument,
+				24 => Error::TooManyFiles,
+				28 => Error::NoSpace,
+				29 => Error::IllegalSeek,
+				30 => Error::ReadOnlyFilesystem,
+				32 => Error::BrokenPipe,
+				38 => Error::UnknownSyscall,
+				39 => Error::DirectoryNotEmpty,
+				_ => return None
+			})
+		}
+
+		/*
+		 * The value returned by the syscall.
+		 */
+		pub const fn to_return_value(self) -> u64 {
+			self.code().wrapping_neg()
+		}
+	}
+
+	/*
+	 * Converts the result of a handler to the value returned by the syscall.
+	 */
+	pub const fn encode(result: Result<u64, Error>) -> u64 {
+		match result {
+			Ok(value) => value,
+			Err(error) => error.to_return_value()
+		}
+	}
+
+	/*
+	 * Splits a value returned by a syscall into the result or the error code.
+	 * Codes, which this version doesn´t know, are kept as they are.
+	 */
+	pub const fn decode(value: u64) -> Result<u64, u64> {
+		if value.wrapping_neg() <= MAX_CODE && value != 0 {
+			Err(value.wrapping_neg())
+		} else {
+			Ok(value)
+		}
+	}
+}
+
+pub mod process {
+	// Waits for any child.
+	pub const ANY_CHILD: u64 = u64::MAX;
+
+	// Priority classes
+	pub const PRIORITY_REALTIME: u64 = 0;
+	pub const PRIORITY_NORMAL: u64 = 1;
+	pub const PRIORITY_IDLE: u64 = 2;
+
+	// Process types
+	pub const TYPE_INIT: u64 = 0;
+	pub const TYPE_BOOT: u64 = 1;
+	pub const TYPE_NORMAL: u64 = 2;
+
+	// Process states
+	pub const STATE_RUNNING: u64 = 0;
+	pub const STATE_KILLED: u64 = 1;
+
+	/*
+	 * Entry of the process list. Parents of processes without
+	 * parent are u64::MAX.
+	 */
+	#[derive(Clone, Copy, Default)]
+	#[repr(C)]
+	pub struct ProcessRecord {
+		pub pid: u64,
+		pub parent: u64,
+		pub r#type: u64,
+		pub state: u64,
+		pub flags: u64,
+		pub threads: u64,
+		// In nanoseconds.
+		pub cpu_time: u64,
+		// Bytes of the mappings.
+		pub mapped_memory: u64
+	}
+
+	/*
+	 * UTF-8 string in user memory.
+	 */
+	#[derive(Clone, Copy)]
+	#[repr(C)]
+	pub struct StringRef {
+		pub address: u64,
+		pub length: u64
+	}
+
+	impl StringRef {
+		pub fn new(string: &str) -> StringRef {
+			StringRef {
+				address: string.as_ptr() as u64,
+				length: string.len() as u64
+			}
+		}
+	}
+
+	/*
+	 * Arguments and environment variables of EXEC. Both point
+	 * to arrays of StringRefs with the amount of entries.
+	 *
+	 * The program starts with the System V ABI stack: The stack
+	 * pointer points to argc, followed by the NULL terminated argv
+	 * and envp arrays of pointers to NUL terminated strings and the
+	 * auxiliary vector of (type, value) pairs ending with AT_NULL.
+	 */
+	#[derive(Clone, Copy)]
+	#[repr(C)]
+	pub struct ExecArguments {
+		pub arguments: u64,
+		pub argument_amount: u64,
+		pub environment: u64,
+		pub environment_amount: u64
+	}
+
+	// Types of auxiliary vector entries, they match the ones of POSIX systems.
+	pub const AT_NULL: u64 = 0;
+	pub const AT_PHDR: u64 = 3;
+	pub const AT_PHENT: u64 = 4;
+	pub const AT_PHNUM: u64 = 5;
+	pub const AT_PAGESZ: u64 = 6;
+	pub const AT_ENTRY: u64 = 9;
+}
+
+pub mod signal {
+	// Numbers match the signals of POSIX systems on x86_64.
+	pub const SIGILL: u64 = 4;
+	pub const SIGTRAP: u64 = 5;
+	pub const SIGBUS: u64 = 7;
+	pub const SIGFPE: u64 = 8;
+	pub const SIGKILL: u64 = 9;
+	pub const SIGSEGV: u64 = 11;
+	pub const SIGPIPE: u64 = 13;
+	pub const SIGCHLD: u64 = 17;
+	pub const SIGNAL_AMOUNT: usize = 64;
+
+	/*
+	 * Processes killed by a signal exit with this code plus the signal.
+	 */
+	pub const SIGNAL_EXIT_CODE: u64 = 0x80;
+
+	// Handlers with a special meaning.
+	pub const SIG_DFL: u64 = 0x0;
+	pub const SIG_IGN: u64 = 0x1;
+}
+
+pub mod clock {
+	// Clock ids match the ones of POSIX systems.
+	pub const CLOCK_REALTIME: u64 = 0;
+	pub const CLOCK_MONOTONIC: u64 = 1;
+
+	pub const NS_PER_SECOND: u64 = 1000000000;
+
+	#[derive(Clone, Copy, Default)]
+	#[repr(C)]
+	pub struct Timespec {
+		pub seconds: u64,
+		pub nanoseconds: u64
+	}
+
+	impl Timespec {
+		pub const fn from_ns(ns: u64) -> Timespec {
+			Timespec {
+				seconds: ns / NS_PER_SECOND,
+				nanoseconds: ns % NS_PER_SECOND
+			}
+		}
+	}
+}
+
+pub mod file {
+	// Open flags
+	pub const OPEN_READ: u64 = 0x1;
+	pub const OPEN_WRITE: u64 = 0x2;
+	// Creates the file, if it doesn´t exist.
+	pub const OPEN_CREATE: u64 = 0x4;
+	// Truncates the file to zero bytes. Requires OPEN_WRITE.
+	pub const OPEN_TRUNCATE: u64 = 0x8;
+
+	// Origins of LSEEK, they match the ones of POSIX systems.
+	pub const SEEK_SET: u64 = 0;
+	pub const SEEK_CUR: u64 = 1;
+	pub const SEEK_END: u64 = 2;
+
+	// Descriptors, which programs started by EXEC inherit.
+	pub const STDIN: u64 = 0;
+	pub const STDOUT: u64 = 1;
+	pub const STDERR: u64 = 2;
+	pub const STDIO_AMOUNT: u64 = 3;
+	// Leaves a descriptor of EXEC closed.
+	pub const NO_FILE: u64 = u64::MAX;
+
+	// File kinds
+	pub const KIND_FILE: u64 = 0;
+	pub const KIND_DIRECTORY: u64 = 1;
+	pub const KIND_PIPE: u64 = 2;
+	pub const KIND_SHARED_MEMORY: u64 = 3;
+
+	// Attributes of files, they match the ones of FAT.
+	pub const ATTRIBUTE_READ_ONLY: u64 = 0x01;
+	pub const ATTRIBUTE_HIDDEN: u64 = 0x02;
+	pub const ATTRIBUTE_SYSTEM: u64 = 0x04;
+	pub const ATTRIBUTE_ARCHIVE: u64 = 0x20;
+
+	// Names are UTF-8, longer ones are cut off.
+	pub const MAX_NAME_LENGTH: usize = 768;
+
+	/*
+	 * Times are nanoseconds since the unix epoch, zero if they´re unknown.
+	 */
+	#[derive(Clone, Copy, Default)]
+	#[repr(C)]
+	pub struct FileStat {
+		// In bytes.
+		pub size: u64,
+		pub kind: u64,
+		pub attributes: u64,
+		pub created: u64,
+		pub modified: u64,
+		pub accessed: u64
+	}
+
+	/*
+	 * Entry of a directory listed by READDIR.
+	 */
+	#[derive(Clone, Copy)]
+	#[repr(C)]
+	pub struct DirectoryRecord {
+		pub stat: FileStat,
+		pub name_length: u64,
+		pub name: [u8; MAX_NAME_LENGTH]
+	}
+
+	impl DirectoryRecord {
+		pub const fn empty() -> DirectoryRecord {
+			DirectoryRecord {
+				stat: FileStat {
+					size: 0,
+					kind: 0,
+					attributes: 0,
+					created: 0,
+					modified: 0,
+					accessed: 0
+				},
+				name_length: 0,
+				name: [0; MAX_NAME_LENGTH]
+			}
+		}
+
+		/*
+		 * Record with the name, which is cut off after MAX_NAME_LENGTH bytes.
+		 */
+		pub fn new(name: &[u8], stat: FileStat) -> DirectoryRecord {
+			let mut record = DirectoryRecord::empty();
+			let length = name.len().min(MAX_NAME_LENGTH);
+			record.name[..length].copy_from_slice(&name[..length]);
+			record.name_length = length as u64;
+			record.stat = stat;
+			record
+		}
+
+		/*
+		 * Names, which were cut off inside of a character, end before it.
+		 */
+		pub fn name(&self) -> &str {
+			let name = &self.name[..(self.name_length as usize).min(MAX_NAME_LENGTH)];
+			match core::str::from_utf8(name) {
+				Ok(name) => name,
+				Err(error) => core::str::from_utf8(&name[..error.valid_up_to()]).unwrap_or("")
+			}
+		}
+	}
+}
+
+pub mod memory {
+	pub const PAGE_SIZE: u64 = 0x1000;
+	pub const MAX_SHARED_MEMORY_SIZE: u64 = 0x10000000;
+
+	// Flags of SHM_MAP
+	pub const MAP_WRITE: u64 = 0x1;
+
+	// Protections of MMAP and MPROTECT, they match the ones of POSIX
+	// systems. Mappings are always readable.
+	pub const PROT_READ: u64 = 0x1;
+	pub const PROT_WRITE: u64 = 0x2;
+	pub const PROT_EXEC: u64 = 0x4;
+}
+
+pub mod ipc {
+	pub const MAX_MESSAGE_SIZE: u64 = 0x1000;
+	// Messages, which a port queues, before senders block.
+	pub const MAX_QUEUED_MESSAGES: usize = 16;
+	// Id of messages, which were sent without waiting for a reply.
+	pub const NO_REPLY: u64 = 0;
+
+	/*
+	 * Written by PORT_RECEIVE. The id is passed to PORT_REPLY.
+	 */
+	#[derive(Clone, Copy, Default)]
+	#[repr(C)]
+	pub struct MessageInfo {
+		pub id: u64,
+		// Pid of the sending process.
+		pub sender: u64,
+		// Length of the whole message, even if it was truncated.
+		pub length: u64
+	}
+}
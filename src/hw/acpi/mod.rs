@@ -3,6 +3,7 @@ mod handler;
 use handler::AcpiMemoryHandler;
 use crate::{
 	uefi_result,
+	hw::clock
 };
 use crate::std::{
 	self,
@@ -15,6 +16,7 @@ use crate::std::{
 use acpi::{
 	AcpiTables,
 	PhysicalMapping,
+	HpetInfo,
 
 	mcfg::Mcfg,
 	madt::Madt,
@@ -93,6 +95,7 @@ impl PCIMCFGEntry {
  * Boot task, that reads some of the ACPI tables.
  * If no ACPI is found, a kernel panic will be produced
  * due to design, but it can be fixed later.
+ * The kernel clock is calibrated with the HPET, if there´s one.
  */
 pub fn setup() -> ! {
 	log::info!("Setting up ACPI.");
@@ -102,6 +105,9 @@ pub fn setup() -> ! {
 			uefi_result!().unwrap().config.acpi as usize
 		).expect("No ACPI found.")
 	};
+	if let Ok(hpet) = HpetInfo::new(&table) {
+		clock::calibrate_with_hpet(hpet.base_address as u64);
+	}
 	*ACPI_SINGLETON.lock() = Some(ACPI {
 		mcfg_mapping: table.find_table::<Mcfg>().ok(),
 		madt_mapping: table.find_table::<Madt>().ok(),
@@ -113,11 +119,10 @@ pub fn setup() -> ! {
 }
 
 /*
- * Reads the ACPI information singleton. The guard will
- * lock the process when accessing the ACPI information, while
- * the ACPI table isn´t initalized.
+ * Reads the ACPI information singleton. The process is
+ * blocked, while the ACPI table isn´t initalized.
  */
 pub fn acpi_singleton() -> std::OptMutexGuard<'static, ACPI> {
-	ACPI_SETUP.lock();
+	ACPI_SETUP.wait();
 	ACPI_SINGLETON.lock_opt()
 }
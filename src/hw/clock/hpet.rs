@@ -0,0 +1,76 @@
+use crate::std::{
+	self,
+	Box,
+	log
+};
+use super::{
+	NS_PER_MS,
+	set_tsc_frequency
+};
+
+/*
+ * Memory mapped HPET registers. Only the main counter is used.
+ */
+#[repr(C)]
+struct HPETRegisters {
+	capabilities: u64,
+	reserved: u64,
+	configuration: u64,
+	reserved_2: u64,
+	interrupt_status: u64,
+	reserved_3: [u64; 25],
+	main_counter: u64
+}
+
+// Configuration: The main counter runs.
+const ENABLE_COUNTER: u64 = 0x1;
+const FS_PER_NS: u64 = 1000000;
+const CALIBRATION_MS: u64 = 50;
+
+/*
+ * Measures the TSC frequency again with the HPET at the physical
+ * address. The HPET is more precise than the PIT, but it´s only
+ * known after the ACPI tables are read. Preemption doesn´t
+ * affect the result, because the TSC and the HPET are always
+ * read together with disabled interrupts.
+ */
+pub fn calibrate_with_hpet(base_address: u64) {
+	let mut registers: Box<HPETRegisters> = Box::from_raw_address(base_address);
+	// Bits 32-63 contain the counter period in femtoseconds.
+	let period = registers.capabilities >> 32;
+	if period == 0 {
+		log::info!("HPET reports no counter period, keeping PIT calibration.");
+		return;
+	}
+	registers.configuration |= ENABLE_COUNTER;
+
+	let ticks = CALIBRATION_MS * NS_PER_MS * FS_PER_NS / period;
+	let (start_counter, start_tsc) = read_counters(&registers);
+	let (mut counter, mut tsc) = (start_counter, start_tsc);
+	while counter.wrapping_sub(start_counter) < ticks {
+		core::hint::spin_loop();
+		(counter, tsc) = read_counters(&registers);
+	}
+	let elapsed_tsc = tsc - start_tsc;
+	let elapsed_fs = counter.wrapping_sub(start_counter) as u128 * period as u128;
+
+	let frequency = (elapsed_tsc as u128 * (super::NS_PER_SECOND * FS_PER_NS) as u128 / elapsed_fs) as u64;
+	set_tsc_frequency(frequency);
+	log::info!("Calibrated TSC with HPET: {} kHz.", frequency / 1000);
+}
+
+/*
+ * Reads the HPET main counter and the TSC.
+ */
+fn read_counters(registers: &HPETRegisters) -> (u64, u64) {
+	let interrupts = std::interrupts_enabled();
+	std::cli();
+	let counter = unsafe {
+		core::ptr::read_volatile(&registers.main_counter)
+	};
+	let tsc = std::rdtsc();
+	if interrupts {
+		std::sti();
+	}
+	(counter, tsc)
+}
@@ -0,0 +1,118 @@
+/*
+ * Kernel clock. The monotonic time is read from the TSC, whose
+ * frequency is measured with the PIT, when the LAPIC timer is
+ * calibrated, and measured again with the HPET, if ACPI reports one.
+ * The realtime is the CMOS RTC time at boot plus the monotonic time.
+ */
+mod hpet;
+mod rtc;
+
+pub use hpet::calibrate_with_hpet;
+pub use rtc::{
+	days_since_epoch,
+	date_from_days
+};
+
+use crate::std::{
+	self,
+	log
+};
+use core::sync::atomic::{
+	AtomicU64,
+	Ordering
+};
+use abi::clock::{
+	CLOCK_REALTIME,
+	CLOCK_MONOTONIC
+};
+
+pub use abi::clock::NS_PER_SECOND;
+pub const NS_PER_MS: u64 = 1000000;
+
+/*
+ * The clock is read lock free. Writers make the sequence odd,
+ * while they change the base, so readers retry.
+ */
+static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+static BASE_TSC: AtomicU64 = AtomicU64::new(0);
+static BASE_NS: AtomicU64 = AtomicU64::new(0);
+// TSC ticks per second. Zero means, that the TSC isn´t calibrated yet.
+static TSC_FREQUENCY: AtomicU64 = AtomicU64::new(0);
+// Nanoseconds since the unix epoch at monotonic time zero.
+static BOOT_REALTIME: AtomicU64 = AtomicU64::new(0);
+
+/*
+ * Reads the realtime clock. Requires a calibrated TSC.
+ */
+pub fn setup() {
+	let unix_time = rtc::read_unix_time();
+	BOOT_REALTIME.store(
+		(unix_time * NS_PER_SECOND).saturating_sub(monotonic_ns()),
+		Ordering::SeqCst
+	);
+	log::info!("Realtime clock: {} seconds since the unix epoch.", unix_time);
+}
+
+/*
+ * Nanoseconds since the TSC was calibrated. Returns zero before.
+ */
+pub fn monotonic_ns() -> u64 {
+	loop {
+		let sequence = SEQUENCE.load(Ordering::SeqCst);
+		if sequence & 0x1 == 0 {
+			let base_tsc = BASE_TSC.load(Ordering::SeqCst);
+			let base_ns = BASE_NS.load(Ordering::SeqCst);
+			let frequency = TSC_FREQUENCY.load(Ordering::SeqCst);
+			if SEQUENCE.load(Ordering::SeqCst) == sequence {
+				return if frequency == 0 {
+					0
+				} else {
+					base_ns + (std::rdtsc().saturating_sub(base_tsc) as u128 * NS_PER_SECOND as u128 / frequency as u128) as u64
+				};
+			}
+		}
+		core::hint::spin_loop();
+	}
+}
+
+/*
+ * Nanoseconds since the unix epoch.
+ */
+pub fn realtime_ns() -> u64 {
+	BOOT_REALTIME.load(Ordering::SeqCst) + monotonic_ns()
+}
+
+/*
+ * Returns the time of the clock with the given id or None
+ * for unknown clocks.
+ */
+pub fn clock_time(clock_id: u64) -> Option<u64> {
+	match clock_id {
+		CLOCK_REALTIME => Some(realtime_ns()),
+		CLOCK_MONOTONIC => Some(monotonic_ns()),
+		_ => None
+	}
+}
+
+/*
+ * Sets the measured TSC frequency in ticks per second. The time
+ * measured so far is kept, so the clock never goes backwards.
+ * Interrupts are disabled meanwhile, because an interrupt reading
+ * the clock would wait forever for the sequence.
+ */
+pub fn set_tsc_frequency(frequency: u64) {
+	let interrupts = std::interrupts_enabled();
+	std::cli();
+	let tsc = std::rdtsc();
+	let now = monotonic_ns();
+
+	SEQUENCE.fetch_add(1, Ordering::SeqCst);
+	BASE_TSC.store(tsc, Ordering::SeqCst);
+	BASE_NS.store(now, Ordering::SeqCst);
+	TSC_FREQUENCY.store(frequency.max(1), Ordering::SeqCst);
+	SEQUENCE.fetch_add(1, Ordering::SeqCst);
+
+	if interrupts {
+		std::sti();
+	}
+}
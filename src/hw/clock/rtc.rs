@@ -0,0 +1,129 @@
+/*
+ * CMOS real time clock. It´s only read once at boot,
+ * later the realtime is derived from the monotonic clock.
+ */
+use crate::std;
+
+const CMOS_ADDRESS: u16 = 0x70;
+const CMOS_DATA: u16 = 0x71;
+
+const RTC_SECONDS: u8 = 0x0;
+const RTC_MINUTES: u8 = 0x2;
+const RTC_HOURS: u8 = 0x4;
+const RTC_DAY: u8 = 0x7;
+const RTC_MONTH: u8 = 0x8;
+const RTC_YEAR: u8 = 0x9;
+const RTC_STATUS_A: u8 = 0xa;
+const RTC_STATUS_B: u8 = 0xb;
+
+// Status A: The RTC updates its registers.
+const UPDATE_IN_PROGRESS: u8 = 0x80;
+// Status B: Hours use 24 hour mode, values are binary instead of BCD.
+const HOURS_24: u8 = 0x2;
+const BINARY_MODE: u8 = 0x4;
+// Set in the hours register for PM in 12 hour mode.
+const HOUR_PM: u8 = 0x80;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+struct RTCTime {
+	seconds: u8,
+	minutes: u8,
+	hours: u8,
+	day: u8,
+	month: u8,
+	year: u8
+}
+
+/*
+ * Reads the RTC time in seconds since the unix epoch.
+ * The RTC is read until two reads match, because an
+ * update may start while reading. The century register
+ * isn´t standardized, so the years 2000-2099 are assumed.
+ */
+pub fn read_unix_time() -> u64 {
+	let mut time = read_time();
+	loop {
+		let next = read_time();
+		if next == time {
+			break;
+		}
+		time = next;
+	}
+
+	let status = read_register(RTC_STATUS_B);
+	let pm = time.hours & HOUR_PM != 0;
+	time.hours &= !HOUR_PM;
+	if status & BINARY_MODE == 0 {
+		time.seconds = from_bcd(time.seconds);
+		time.minutes = from_bcd(time.minutes);
+		time.hours = from_bcd(time.hours);
+		time.day = from_bcd(time.day);
+		time.month = from_bcd(time.month);
+		time.year = from_bcd(time.year);
+	}
+	if status & HOURS_24 == 0 {
+		time.hours %= 12;
+		if pm {
+			time.hours += 12;
+		}
+	}
+
+	let days = days_since_epoch(2000 + time.year as u64, time.month as u64, time.day as u64);
+	((days * 24 + time.hours as u64) * 60 + time.minutes as u64) * 60 + time.seconds as u64
+}
+
+fn read_time() -> RTCTime {
+	while read_register(RTC_STATUS_A) & UPDATE_IN_PROGRESS != 0 {
+		core::hint::spin_loop();
+	}
+	RTCTime {
+		seconds: read_register(RTC_SECONDS),
+		minutes: read_register(RTC_MINUTES),
+		hours: read_register(RTC_HOURS),
+		day: read_register(RTC_DAY),
+		month: read_register(RTC_MONTH),
+		year: read_register(RTC_YEAR)
+	}
+}
+
+fn read_register(register: u8) -> u8 {
+	// Bit 7 keeps NMIs enabled.
+	std::outb(register & 0x7f, CMOS_ADDRESS);
+	std::inb(CMOS_DATA)
+}
+
+fn from_bcd(value: u8) -> u8 {
+	(value >> 4) * 10 + (value & 0xf)
+}
+
+/*
+ * Days from 1970-01-01 to the date of the proleptic
+ * gregorian calendar. Months and days start with one.
+ */
+pub fn days_since_epoch(year: u64, month: u64, day: u64) -> u64 {
+	// Years start in march, so leap days are at the end of the year.
+	let year = if month <= 2 { year - 1 } else { year };
+	let era = year / 400;
+	let year_of_era = year % 400;
+	let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day.max(1) - 1;
+	let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+	era * 146097 + day_of_era - 719468
+}
+
+/*
+ * Date of the day since 1970-01-01 in the proleptic
+ * gregorian calendar. Months and days start with one.
+ */
+pub fn date_from_days(days: u64) -> (u64, u64, u64) {
+	// Years start in march, so leap days are at the end of the year.
+	let days = days + 719468;
+	let era = days / 146097;
+	let day_of_era = days % 146097;
+	let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let month_from_march = (5 * day_of_year + 2) / 153;
+	let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+	let month = if month_from_march < 10 { month_from_march + 3 } else { month_from_march - 9 };
+	let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+	(year, month, day)
+}
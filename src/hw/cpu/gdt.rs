@@ -228,3 +228,11 @@ pub fn per_core_setup() {
 	gdt.load();
 	gdt.setup_tss();
 }
+
+/*
+ * Sets the stack, the current core switches to, when an
+ * interrupt or exception arrives in user mode.
+ */
+pub fn set_kernel_stack(stack: u64) {
+	GDTS.deref_mut().tss.ring_rsps[0] = stack;
+}
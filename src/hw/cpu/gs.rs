@@ -14,7 +14,9 @@ use core::{
 pub struct GSContentRaw {
 	pub syscall_stack: *mut u8,
 	pub core_idx: u64,
-	pub signature: u64
+	pub signature: u64,
+	// Scratch slot for the user stack pointer on syscall entry.
+	pub user_stack: u64
 }
 
 /*
@@ -55,7 +57,8 @@ impl Default for GSContent {
 			raw: GSContentRaw {
 				syscall_stack: syscall_stack.as_stack(),
 				core_idx: current_core_uncached() as u64,
-				signature: 0x8988d80d6631faec
+				signature: 0x8988d80d6631faec,
+				user_stack: 0x0
 			},
 			syscall_stack
 		}
@@ -87,3 +90,29 @@ pub fn init() {
 pub fn current_core() -> Option<u64> {
 	gs_read!(core_idx, u64)
 }
+
+/*
+ * Returns the raw gs base of the current core.
+ */
+pub fn base() -> u64 {
+	let gsbase: u64;
+	unsafe {
+		asm!("rdgsbase {}",
+			 out(reg) gsbase);
+	}
+	gsbase
+}
+
+/*
+ * Sets the stack, which is used by the next syscall
+ * on the current core. Ignored, when the gs hasn´t been
+ * set up.
+ */
+pub fn set_syscall_stack(stack: u64) {
+	let gsbase = base();
+	if gs_read!(signature, u64).is_some() {
+		unsafe {
+			(*(gsbase as *mut GSContentRaw)).syscall_stack = stack as *mut u8;
+		}
+	}
+}
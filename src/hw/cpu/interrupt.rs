@@ -9,8 +9,8 @@ use super::{
 	gdt::GDT,
 	lapic
 };
-use core::ops::Deref;
 use crate::assume_safe_asm;
+use core::arch::naked_asm;
 
 #[derive(Copy, Clone)]
 #[repr(C, packed)]
@@ -36,21 +36,40 @@ struct IDTR {
 	base: u64
 }
 
-#[derive(Clone, Copy)]
+/*
+ * State of the code, which caused an exception. The general purpose
+ * registers are saved by exception_entry, the rest by the CPU.
+ * Changes are applied, when the exception methods return.
+ */
 #[repr(C)]
-pub struct InterruptFrame {
+pub struct ExceptionFrame {
+	pub rax: u64,
+	pub rbx: u64,
+	pub rcx: u64,
+	pub rdx: u64,
+	pub rsi: u64,
+	pub rdi: u64,
+	pub rbp: u64,
+	pub r8: u64,
+	pub r9: u64,
+	pub r10: u64,
+	pub r11: u64,
+	pub r12: u64,
+	pub r13: u64,
+	pub r14: u64,
+	pub r15: u64,
+	pub vector: u64,
+	pub error: u64,
 	pub rip: u64,
-	pub cs: u16,
-	_reserved: [u8; 6],
+	pub cs: u64,
 	pub rflags: u64,
 	pub rsp: u64,
-	pub ss: u16,
-	_reserved2: [u8; 6]
+	pub ss: u64
 }
 
 type Handler = extern "x86-interrupt" fn();
 type SignalMethod = fn(u8);
-type ExceptionMethod = fn(u8, InterruptFrame, u64);
+type ExceptionMethod = fn(&mut ExceptionFrame);
 
 macro_rules! define_interrupt_handler_method {
 	($index:literal) => {
@@ -62,15 +81,24 @@ macro_rules! define_interrupt_handler_method {
 	};
 	($index:literal, exception) => {
 		paste! {
-			extern "x86-interrupt" fn [<interrupt_handler_ $index>](mut frame: InterruptFrame) {
-				handle_exception($index, &mut frame, 0)
+			#[unsafe(naked)]
+			extern "sysv64" fn [<interrupt_handler_ $index>]() {
+				naked_asm!("push 0x0",
+						   "push {}",
+						   "jmp {}",
+						   const $index,
+						   sym exception_entry)
 			}
 		}
 	};
 	($index:literal, exception_with_err) => {
 		paste! {
-			extern "x86-interrupt" fn [<interrupt_handler_ $index>](mut frame: InterruptFrame, err: u64) {
-				handle_exception($index, &mut frame, err)
+			#[unsafe(naked)]
+			extern "sysv64" fn [<interrupt_handler_ $index>]() {
+				naked_asm!("push {}",
+						   "jmp {}",
+						   const $index,
+						   sym exception_entry)
 			}
 		}
 	};
@@ -147,7 +175,18 @@ define_interrupt_handler_list! {
 	0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xff
 }
 
-pub const TIMER: usize = 0x32;
+/*
+ * Vector of the LAPIC timer. It´s below the IOAPIC vectors.
+ */
+pub const TIMER: usize = 0x20;
+/*
+ * Vector of the PS/2 keyboard (IRQ 1), see IOAPIC::activate.
+ */
+pub const KEYBOARD: usize = 0x31;
+/*
+ * Vector, which other cores send to flush the TLB, see mm::shootdown_tlb.
+ */
+pub const TLB_SHOOTDOWN: usize = 0x40;
 static IDTS: PerCpuLazy<IDT> = PerCpuLazy::new(IDT::new);
 static INTERRUPT_CONNECTION_METHS: Mutex<[Vec<SignalMethod>; 0x100]> = Mutex::new(
 	[const { Vec::new() }; 0x100]
@@ -218,50 +257,105 @@ pub fn current_idt() -> &'static mut IDT {
 
 /*
  * index contains the IDT vector, that will be connected
- * to the signal.
+ * to the signal. Only the IDT of the current core is connected,
+ * so call this method on every core, which should receive the
+ * signal. A method is only added once.
  */
 pub fn connect_signal(index: usize, meth: SignalMethod) {
+	IDTS.deref_mut().connect_handler(
+		index,
+		INTERRUPT_HANDLERS[index]
+	);
+
 	let mut lock = INTERRUPT_CONNECTION_METHS.lock();
-	if lock.len() == 0 {
-		IDTS.deref_mut().connect_handler(
-			index,
-			INTERRUPT_HANDLERS[index]
-		);
+	if !(&lock[index]).into_iter().any(|connected| *connected as usize == meth as usize) {
+		lock[index].push_back(meth);
 	}
-
-	lock[index].push_back(meth);
 }
 
 /*
  * Adds an exception handler.
  * The methods will be called after an expcetion.
  * A panic will happen if no exception handlers are
- * added and a exception happens or a exception happens
- * in a exception handler.
+ * added and a exception happens. A method is only added once.
  */
 pub fn connect_exception(meth: ExceptionMethod) {
-	EXCEPTION_CONNECTION_METHS.lock().push_back(meth);
+	let mut lock = EXCEPTION_CONNECTION_METHS.lock();
+	if !(&*lock).into_iter().any(|connected| *connected as usize == meth as usize) {
+		lock.push_back(meth);
+	}
 }
 
+/*
+ * The interrupt is acknowledged before calling the methods,
+ * because a method may switch to another task (the timer for example)
+ * and return much later.
+ */
 fn handle_interrupt(vector: u8) {
-	let methods = &INTERRUPT_CONNECTION_METHS.lock()[vector as usize];
+	lapic::LAPIC::end_of_interrupt();
+
+	let methods = &INTERRUPT_CONNECTION_METHS.read()[vector as usize];
 	for method in methods.into_iter() {
 		method(vector);
 	}
-	lapic::LAPIC::end_of_interrupt();
 }
 
-fn handle_exception(vector: u8, frame: &mut InterruptFrame, err_code: u64) {
-	let exception_meths = EXCEPTION_CONNECTION_METHS.try_lock();
-	if let Some(methods) = exception_meths.as_ref() {
-		if methods.deref().len() == 0 {
-			panic!("Fatal exception with no exception handler.");
-		}
-		for method in methods.deref() {
-			method(vector, *frame, err_code);
-		}
-	} else {
-		panic!("Fatal exception in exception handler.");
+/*
+ * Common part of the exception handlers. The handlers push
+ * the vector and a zero, if the CPU pushed no error code.
+ * Then the general purpose registers are pushed to complete
+ * the ExceptionFrame.
+ */
+#[unsafe(naked)]
+extern "sysv64" fn exception_entry() {
+	naked_asm!("push r15",
+			   "push r14",
+			   "push r13",
+			   "push r12",
+			   "push r11",
+			   "push r10",
+			   "push r9",
+			   "push r8",
+			   "push rbp",
+			   "push rdi",
+			   "push rsi",
+			   "push rdx",
+			   "push rcx",
+			   "push rbx",
+			   "push rax",
+			   "mov rdi, rsp",
+			   "call {}",
+			   "pop rax",
+			   "pop rbx",
+			   "pop rcx",
+			   "pop rdx",
+			   "pop rsi",
+			   "pop rdi",
+			   "pop rbp",
+			   "pop r8",
+			   "pop r9",
+			   "pop r10",
+			   "pop r11",
+			   "pop r12",
+			   "pop r13",
+			   "pop r14",
+			   "pop r15",
+			   "add rsp, 0x10",
+			   "iretq",
+			   sym handle_exception)
+}
+
+/*
+ * The methods aren´t locked, because exceptions may happen on
+ * several cores at once and a method may not return.
+ */
+extern "sysv64" fn handle_exception(frame: &mut ExceptionFrame) {
+	let methods = EXCEPTION_CONNECTION_METHS.read();
+	if methods.len() == 0 {
+		panic!("Fatal exception with no exception handler.");
+	}
+	for method in methods {
+		method(frame);
 	}
 
 	lapic::LAPIC::end_of_interrupt();
@@ -2,14 +2,23 @@ use core::ops::{
 	Deref,
 	DerefMut
 };
+use core::ptr;
+use core::sync::atomic::{
+	AtomicU32,
+	AtomicU64,
+	Ordering
+};
 use crate::std::{
 	LazyMutex,
+	Lock,
 	Box,
 	rdmsr
 };
+use super::TIMER;
 use crate::{
 	std,
 	lapic,
+	hw::clock
 };
 
 struct LAPICRegister {
@@ -41,7 +50,17 @@ pub struct LAPIC {
 	error_status: LAPICRegister,
 	reserved_3: [LAPICRegister; 7],
 	command_register_1: LAPICRegister,
-	command_register_2: LAPICRegister
+	command_register_2: LAPICRegister,
+	lvt_timer: LAPICRegister,
+	lvt_thermal_sensor: LAPICRegister,
+	lvt_performance_counter: LAPICRegister,
+	lvt_lint0: LAPICRegister,
+	lvt_lint1: LAPICRegister,
+	lvt_error: LAPICRegister,
+	timer_initial_count: LAPICRegister,
+	timer_current_count: LAPICRegister,
+	reserved_4: [LAPICRegister; 4],
+	timer_divide_configuration: LAPICRegister
 }
 
 /*
@@ -59,6 +78,26 @@ pub static LAPICS: LazyMutex<Box<LAPIC>> = LazyMutex::new(
 pub static IOAPIC: LazyMutex<Box<IOAPIC>> = LazyMutex::new(
 	|| Box::from_raw_address(0xfec00000)
 );
+/*
+ * LAPIC timer ticks per millisecond with a divider of 16.
+ * Zero means, that the timer isn´t calibrated yet.
+ */
+static TIMER_TICKS_PER_MS: AtomicU32 = AtomicU32::new(0);
+// Serializes the calibration of the cores booting at the same time.
+static CALIBRATION: Lock = Lock::new();
+/*
+ * Virtual address of the mapped LAPIC page. Every core sees its own
+ * LAPIC at this address, so the timer of the current core is
+ * programmed without the global lock.
+ * Zero means, that no core enabled its LAPIC yet.
+ */
+static LOCAL_LAPIC: AtomicU64 = AtomicU64::new(0);
+
+/*
+ * PIT ticks of 10 ms. The PIT runs with 1.193182 MHz.
+ */
+const PIT_CALIBRATION_COUNT: u16 = 11932;
+const PIT_CALIBRATION_MS: u32 = 10;
 
 impl LAPIC {
 	/*
@@ -93,6 +132,93 @@ impl LAPIC {
 		*lapiclock.task_priority = 0x0;
 
 		*lapiclock.spurious_interrupt_vector = 0x11ff;
+		LOCAL_LAPIC.store(&**lapiclock as *const LAPIC as u64, Ordering::SeqCst);
+	}
+	/*
+	 * Measures the LAPIC timer frequency with a one shot of the
+	 * PIT channel 2. Only the first core measures, every other
+	 * core reuses the result, because all LAPIC timers run with
+	 * the same bus clock. The TSC frequency of the kernel clock
+	 * is measured with the same one shot.
+	 */
+	pub fn calibrate_timer() {
+		CALIBRATION.lock();
+		if TIMER_TICKS_PER_MS.load(Ordering::SeqCst) != 0 {
+			CALIBRATION.unlock();
+			return;
+		}
+		let lapic = Self::local();
+
+		// Gate of channel 2 on, speaker off.
+		std::outb((std::inb(0x61) & !0x2) | 0x1, 0x61);
+		// Channel 2, low and high byte, mode 1 (hardware one shot)
+		std::outb(0xb2, 0x43);
+		std::outb(PIT_CALIBRATION_COUNT as u8, 0x42);
+		std::outb((PIT_CALIBRATION_COUNT >> 8) as u8, 0x42);
+
+		// A rising edge on the gate starts the one shot.
+		let gate = std::inb(0x61) & !0x1;
+		std::outb(gate, 0x61);
+		std::outb(gate | 0x1, 0x61);
+
+		let elapsed;
+		let start_tsc;
+		unsafe {
+			ptr::write_volatile(&raw mut (*lapic).timer_divide_configuration.content, 0x3);
+			ptr::write_volatile(&raw mut (*lapic).lvt_timer.content, 0x10000);
+			ptr::write_volatile(&raw mut (*lapic).timer_initial_count.content, u32::MAX);
+			start_tsc = std::rdtsc();
+
+			while std::inb(0x61) & 0x20 == 0 {}
+
+			elapsed = u32::MAX - ptr::read_volatile(&raw const (*lapic).timer_current_count.content);
+			ptr::write_volatile(&raw mut (*lapic).timer_initial_count.content, 0x0);
+		}
+		let elapsed_tsc = std::rdtsc() - start_tsc;
+		clock::set_tsc_frequency(elapsed_tsc * 1000 / PIT_CALIBRATION_MS as u64);
+		TIMER_TICKS_PER_MS.store((elapsed / PIT_CALIBRATION_MS).max(1), Ordering::SeqCst);
+		CALIBRATION.unlock();
+	}
+	/*
+	 * Arms the timer of the current core in one shot mode.
+	 * The TIMER vector fires after the given amount of milliseconds.
+	 * Requires a calibrated timer.
+	 */
+	pub fn arm_timer(ms: u64) {
+		let ticks = TIMER_TICKS_PER_MS.load(Ordering::Relaxed) as u64 * ms;
+		assert!(ticks != 0, "Attempt to arm uncalibrated LAPIC timer.");
+
+		let lapic = Self::local();
+		unsafe {
+			ptr::write_volatile(&raw mut (*lapic).timer_divide_configuration.content, 0x3);
+			ptr::write_volatile(&raw mut (*lapic).lvt_timer.content, TIMER as u32);
+			ptr::write_volatile(&raw mut (*lapic).timer_initial_count.content, ticks.min(u32::MAX as u64) as u32);
+		}
+	}
+	/*
+	 * Sends the vector as fixed interrupt to the core with the LAPIC id.
+	 */
+	pub fn send_interrupt(core: usize, vector: usize) {
+		let interrupts = std::interrupts_enabled();
+		std::cli();
+		let lapic = Self::local();
+		unsafe {
+			ptr::write_volatile(&raw mut (*lapic).command_register_2.content, (core as u32) << 24);
+			ptr::write_volatile(&raw mut (*lapic).command_register_1.content, 0x4000 | vector as u32);
+		}
+		if interrupts {
+			std::sti();
+		}
+	}
+	/*
+	 * LAPIC of the current core, without taking the global lock.
+	 * Only the timer and the interrupt command registers of the
+	 * current core are accessed this way.
+	 */
+	fn local() -> *mut LAPIC {
+		let address = LOCAL_LAPIC.load(Ordering::Relaxed);
+		assert!(address != 0, "Attempt to access the LAPIC before enabling it.");
+		address as *mut LAPIC
 	}
 	fn send_command(&mut self, command: u32, target: u32) {
 		*self.command_register_2 = target;
@@ -13,8 +13,10 @@ pub use gdt::GDT;
 pub use interrupt::{
 	connect_signal,
 	connect_exception,
-	InterruptFrame,
-	TIMER
+	ExceptionFrame,
+	TIMER,
+	KEYBOARD,
+	TLB_SHOOTDOWN
 };
 
 use crate::std::{
@@ -23,8 +25,10 @@ use crate::std::{
 };
 
 /*
- * Sets up current cores GDT, IDT, LAPIC and
+ * Sets up current cores GDT, IDT, LAPIC, LAPIC timer and
  * a MSR for syscalls activation.
+ * The timer fires after the first time slice, but the signal
+ * is connected later by the scheduler.
  */
 pub fn setup_core() {
 	gdt::per_core_setup();
@@ -34,6 +38,8 @@ pub fn setup_core() {
 	std::cli();
 
 	LAPIC::enable_hardware_interrupts();
+	LAPIC::calibrate_timer();
+	LAPIC::arm_timer(crate::kernel::scheduler::time_slice());
 	syscall::setup();
 }
 
@@ -58,8 +58,9 @@ setup_paging:
 
 	lea page_table_l4, %eax
 	mov %eax, %cr3
+	# Paging and write protection, so copy on write pages are honored by the kernel, too.
 	mov %cr0, %eax
-	or $0x80000000, %eax
+	or $0x80010000, %eax
 	mov %eax, %cr0
 
 	lgdt [gdtr_high]
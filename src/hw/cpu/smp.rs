@@ -41,9 +41,12 @@ pub fn smp_core_meth() -> ! {
 }
 
 fn smp_core_setup() -> ! {
+	cpu::gs::init();
 	crate::kernel::per_core_setup();
+	std::sti();
 
 	loop {
 		std::r#yield();
+		std::hlt();
 	}
 }
@@ -1,61 +1,148 @@
 use crate::std::{
 	Mutex,
-	Vec,
+	KernelError,
+	KernelResult,
 	wrmsr
 };
 use core::arch::{
 	naked_asm
 };
+use abi::{
+	syscall,
+	error
+};
 
-type SyscallMeth = fn(&[u64]) -> u64;
+type SyscallMeth = fn(&[u64]) -> KernelResult<u64>;
 
+/*
+ * Handler of the syscall with the number from abi::syscall.
+ */
 pub struct Function {
-	pub id: u64,
+	pub number: u64,
 	pub meth: SyscallMeth
 }
 
-static FUNCIONALITIES: Mutex<Vec<Function>> = Mutex::new(Vec::new());
+/*
+ * User registers, which __do_syscall saved on the kernel stack.
+ * rcx holds the return address and r11 the flags of the user.
+ */
+#[repr(C)]
+pub struct SyscallFrame {
+	pub r15: u64,
+	pub r14: u64,
+	pub r13: u64,
+	pub r12: u64,
+	pub r11: u64,
+	pub r10: u64,
+	pub rbp: u64,
+	pub rcx: u64,
+	pub rbx: u64,
+	pub rdi: u64,
+	_alignment: u64,
+	pub rsp: u64
+}
+
+/*
+ * Handlers indexed by their syscall number. It´s only
+ * changed during the setup, so syscalls read it without locking.
+ */
+static FUNCIONALITIES: Mutex<[Option<SyscallMeth>; syscall::COUNT]> = Mutex::new([None; syscall::COUNT]);
 
 impl Function {
 	pub fn add(self) {
-		FUNCIONALITIES.lock().push_back(self);
+		let mut functions = FUNCIONALITIES.lock();
+		let slot = &mut functions[self.number as usize];
+		assert!(slot.is_none(), "Syscall number {} has two handlers.", self.number);
+		*slot = Some(self.meth);
 	}
 }
 
-fn finish_functionality_list() {
-	FUNCIONALITIES.lock().sort(|a, b| a.id < b.id)
+impl SyscallFrame {
+	/*
+	 * Returns the frame of the running syscall on the kernel stack with the given top.
+	 */
+	pub fn from_stack(stack_top: u64) -> &'static SyscallFrame {
+		unsafe {
+			((stack_top - core::mem::size_of::<SyscallFrame>() as u64) as *const SyscallFrame)
+				.as_ref()
+				.expect("Syscall frame calculation failed.")
+		}
+	}
+
+	/*
+	 * Registers of the user after the syscall in the order of the task state.
+	 * rdx, rsi, r8 and r9 aren´t preserved by syscalls, so they´re zero.
+	 */
+	pub fn user_registers(&self, return_value: u64) -> [u64; 16] {
+		[
+			return_value, // RAX
+			self.rbx,
+			self.rcx,
+			0x0, // RDX
+			0x0, // RSI
+			self.rdi,
+			self.rbp,
+			self.rsp,
+			0x0, // R8
+			0x0, // R9
+			self.r10,
+			self.r11,
+			self.r12,
+			self.r13,
+			self.r14,
+			self.r15
+		]
+	}
 }
 
 /*
  * Sets up nececary MSRs for syscalls.
+ * Interrupts are masked during syscalls, because the
+ * entry runs on the user stack for a short moment.
  */
 pub fn setup() {
 	wrmsr(0xc0000082, __do_syscall as u64 + crate::mm::kernel_offset()); // LSTAR
 	wrmsr(0xc0000081, 0x8 << 32 | 0x1b << 48);
+	wrmsr(0xc0000084, 0x200); // FMASK
 }
 
 #[unsafe(no_mangle)]
 extern "sysv64" fn do_syscall(function: u64, arg1: u64, arg2: u64, arg3: u64, arg4: u64, arg5: u64, arg6: u64) -> u64 {
-	let lock = FUNCIONALITIES.lock();
-	if let Some(f) = lock.into_iter().find(|a| a.id == function) {
-		let addr = crate::mm::kernel_offset() + f.meth as *const () as u64;
+	if let Some(Some(meth)) = FUNCIONALITIES.read().get(function as usize) {
+		let addr = crate::mm::kernel_offset() + *meth as *const () as u64;
 		let fmeth: SyscallMeth = unsafe {
 			core::mem::transmute(addr)
 		};
-		(fmeth)(&[arg1, arg2, arg3, arg4, arg5, arg6])
+		error::encode((fmeth)(&[arg1, arg2, arg3, arg4, arg5, arg6]))
 	} else {
-		crate::std::log::error!("Invalid syscall opcode: {:x}", function);
-		0x1
+		crate::std::log::error!("Invalid syscall number: {:x}", function);
+		KernelError::UnknownSyscall.to_return_value()
 	}
 }
 
+/*
+ * Called with the return value, before returning to user mode.
+ * Pending signals may redirect the return to their handler.
+ */
+#[unsafe(no_mangle)]
+extern "sysv64" fn finish_syscall(frame: &mut SyscallFrame, return_value: u64) -> u64 {
+	crate::kernel::deliver_signals(frame, return_value)
+}
+
 /*
  * This method should not be called.
+ * The syscall runs on the kernel stack of the calling process
+ * stored in gs:0x0. The user stack pointer is saved on it, so
+ * the process may be switched out during the syscall.
  */
 #[unsafe(naked)]
 pub extern "sysv64" fn __do_syscall() {
 	naked_asm!("swapgs",
-				"xchg rsp, qword ptr gs:0x0",
+				"mov qword ptr gs:0x18, rsp",
+				"mov rsp, qword ptr gs:0x0",
+				"push qword ptr gs:0x18",
+				"sub rsp, 0x8",
+				"push rdi",
 				"push rbx",
 				"push rcx",
 				"push rbp",
@@ -63,10 +150,15 @@ pub extern "sysv64" fn __do_syscall() {
 				"push r11",
 				"push r12",
 				"push r13",
+				"push r14",
 				"push r15",
 				"mov rcx, r14",
 				"call do_syscall",
+				"mov rsi, rax",
+				"mov rdi, rsp",
+				"call finish_syscall",
 				"pop r15",
+				"pop r14",
 				"pop r13",
 				"pop r12",
 				"pop r11",
@@ -74,7 +166,9 @@ pub extern "sysv64" fn __do_syscall() {
 				"pop rbp",
 				"pop rcx",
 				"pop rbx",
-				"xchg rsp, qword ptr gs:0x0",
+				"pop rdi",
+				"add rsp, 0x8",
+				"pop rsp",
 				"swapgs",
 				"sysretq"
 	)
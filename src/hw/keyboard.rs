@@ -0,0 +1,28 @@
+/*
+ * PS/2 keyboard. Only scancodes are read, there´s no keymap yet.
+ * The controller translates the scancodes to set 1.
+ */
+use crate::std;
+
+const DATA_PORT: u16 = 0x60;
+const STATUS_PORT: u16 = 0x64;
+
+// Status: The output buffer contains a byte, which is from the mouse.
+const OUTPUT_FULL: u8 = 0x1;
+const MOUSE_DATA: u8 = 0x20;
+
+// Set 1 make codes.
+pub const SCANCODE_F12: u8 = 0x58;
+
+/*
+ * Reads the next byte of the keyboard. Returns None, if there´s none,
+ * e.g. because another core already took it. The IOAPIC sends
+ * the keyboard interrupt to every core.
+ */
+pub fn read_scancode() -> Option<u8> {
+	let status = std::inb(STATUS_PORT);
+	if status & OUTPUT_FULL == 0 || status & MOUSE_DATA != 0 {
+		return None;
+	}
+	Some(std::inb(DATA_PORT))
+}
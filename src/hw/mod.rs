@@ -4,6 +4,8 @@ pub mod acpi;
 pub mod pci;
 pub mod traits;
 pub mod power;
+pub mod clock;
+pub mod keyboard;
 
 pub use traits::{
 	disk::*
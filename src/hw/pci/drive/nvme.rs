@@ -215,6 +215,17 @@ impl PhysicalDisk for NVMEDrive {
 		));
 		buffer
 	}
+
+	/*
+	 * This method also waits for the sector to be written.
+	 */
+	fn write_lba(&self, lba: usize, sector: &Sector) {
+		self.send_io_command(SubmissionEntry::new_io_write(
+			self.active_namespaces[0] as u32,
+			lba,
+			sector
+		));
+	}
 }
 
 impl Queue {
@@ -316,8 +327,17 @@ impl SubmissionEntry {
 		}
 	}
 	fn new_io_read(namespace: u32, lba: usize, buffer: &Sector) -> SubmissionEntry {
+		SubmissionEntry::new_io_transfer(0x2, namespace, lba, buffer)
+	}
+	fn new_io_write(namespace: u32, lba: usize, buffer: &Sector) -> SubmissionEntry {
+		SubmissionEntry::new_io_transfer(0x1, namespace, lba, buffer)
+	}
+	/*
+	 * Reads or writes one sector.
+	 */
+	fn new_io_transfer(command: u32, namespace: u32, lba: usize, buffer: &Sector) -> SubmissionEntry {
 		SubmissionEntry {
-			command: 0x2,
+			command,
 			nsid: namespace,
 			reserved: 0,
 			metadata: 0,
@@ -328,7 +348,8 @@ impl SubmissionEntry {
 			command_specific: [
 				(lba & 0xffffffff) as u32,
 				(lba >> 32) as u32,
-				1,
+				// The amount of sectors is zero based.
+				0,
 				0,
 				0,
 				0
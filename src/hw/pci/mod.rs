@@ -50,8 +50,7 @@ pub fn scan() -> ! {
  * Waits until the pci scan boot task is completed
  */
 pub fn wait_for_scan() {
-	SCAN_LOCK.lock();
-	SCAN_LOCK.unlock();
+	SCAN_LOCK.wait();
 }
 
 /*
@@ -21,6 +21,7 @@ pub trait PhysicalDisk {
 	 */
 	fn reset(&mut self);
 	fn read_lba(&self, lba: usize) -> Sector;
+	fn write_lba(&self, lba: usize, sector: &Sector);
 }
 
 /*
@@ -65,6 +66,23 @@ pub fn read_lbas(disk_idx: usize, lba: usize, amount: usize) -> Box<[u8]> {
 	r#box
 }
 
+pub fn write_lba(disk_idx: usize, lba: usize, sector: &Sector) {
+	DISKS[disk_idx].physical_disk.write_lba(lba, sector)
+}
+
+/*
+ * Writes the data to consecutive sectors. Its length
+ * has to be a multiple of the sector size.
+ */
+pub fn write_lbas(disk_idx: usize, lba: usize, data: &[u8]) {
+	let mut sector: Sector = Box::new_uninit();
+	for (idx, chunk) in data.chunks_exact(SECTOR_SIZE).enumerate() {
+		sector.copy_from_slice(chunk);
+		write_lba(disk_idx, lba + idx, &sector);
+	}
+	sector.free();
+}
+
 pub fn disk_ids() -> Vec<usize> {
 	(0..DISKS.read().len()).collect()
 }
@@ -3,7 +3,9 @@
  */
 use super::scheduler::{
 	Process,
-	ProcessPrivilage
+	ProcessPrivilage,
+	ProcessPriority,
+	ProcessType
 };
 
 use crate::hw;
@@ -12,8 +14,9 @@ pub struct BootTask(Process);
 
 type BootTaskMeth = fn() -> !;
 
-const BOOT_PROCESSES: [BootTaskMeth; 6] = [
+const BOOT_PROCESSES: [BootTaskMeth; 7] = [
 	super::graphicmanager::setup_console_task,
+	super::debugdump::dump_task,
 	hw::acpi::setup,
 	hw::pci::scan,
 	hw::pci::setup,
@@ -24,13 +27,18 @@ const BOOT_PROCESSES: [BootTaskMeth; 6] = [
 
 impl BootTask {
 	/*
-	 * Spawn boot task process.
+	 * Spawn boot task process. Boot tasks are realtime processes,
+	 * so user processes can´t delay the setup.
 	 */
 	pub fn spawn(meth: BootTaskMeth) {
-		Process::spawn_with_stack(
+		let mut process = Process::new_with_stack(
 			ProcessPrivilage::KERNEL,
-			meth
+			meth,
+			0x50000
 		).expect("Failed to create critical boot task.");
+		process.set_priority(ProcessPriority::REALTIME);
+		process.r#type = ProcessType::BOOT;
+		process.spawn();
 	}
 
 	/*
@@ -0,0 +1,51 @@
+/*
+ * Reference counts of mapping contents, which are shared between
+ * processes after a fork. Contents, that aren´t listed, have a
+ * single owner.
+ */
+
+use crate::std::{
+	Mutex,
+	Vec
+};
+
+struct SharedContent {
+	physical_address: u64,
+	references: usize
+}
+
+static SHARED_CONTENTS: Mutex<Vec<SharedContent>> = Mutex::new(Vec::new());
+
+/*
+ * Adds an owner to the content. Content, which wasn´t
+ * shared before, has two owners afterwards.
+ */
+pub fn share(physical_address: u64) {
+	let mut contents = SHARED_CONTENTS.lock();
+	if let Some(content) = (&mut *contents).into_iter().find(|content| content.physical_address == physical_address) {
+		content.references += 1;
+	} else {
+		contents.push_back(SharedContent {
+			physical_address,
+			references: 2
+		});
+	}
+}
+
+/*
+ * Removes an owner from the content. Returns true, when
+ * the caller was the last owner and may reuse or free it.
+ */
+pub fn release(physical_address: u64) -> bool {
+	let mut contents = SHARED_CONTENTS.lock();
+	let Some(idx) = (&*contents).into_iter().position(|content| content.physical_address == physical_address) else {
+		return true;
+	};
+	contents[idx].references -= 1;
+	if contents[idx].references == 0 {
+		contents.remove(idx);
+		true
+	} else {
+		false
+	}
+}
@@ -0,0 +1,70 @@
+/*
+ * Prints the process table to the console, when F12 is pressed.
+ * The keyboard interrupt only wakes the dump task, because printing
+ * in the interrupt would wait for the console lock, which the
+ * interrupted thread may hold.
+ */
+
+use super::scheduler::process_table;
+use crate::hw::{
+	cpu,
+	keyboard,
+	clock::NS_PER_MS
+};
+use crate::std::{
+	WaitQueue,
+	log
+};
+use crate::println;
+use core::fmt::Write;
+use core::sync::atomic::{
+	AtomicBool,
+	Ordering
+};
+
+static DUMP_REQUESTED: AtomicBool = AtomicBool::new(false);
+static DUMP_REQUESTS: WaitQueue = WaitQueue::new();
+
+/*
+ * Connects the keyboard of the current core with the dump task.
+ */
+pub fn connect_hotkey() {
+	cpu::connect_signal(cpu::KEYBOARD, |_| {
+		if keyboard::read_scancode() == Some(keyboard::SCANCODE_F12) {
+			DUMP_REQUESTED.store(true, Ordering::SeqCst);
+			DUMP_REQUESTS.wake_all();
+		}
+	});
+}
+
+/*
+ * Boot task, that dumps the process table after every request.
+ */
+pub fn dump_task() -> ! {
+	log::info!("Press F12 to dump the process table.");
+	loop {
+		DUMP_REQUESTS.wait_until(|| DUMP_REQUESTED.load(Ordering::SeqCst));
+		DUMP_REQUESTED.store(false, Ordering::SeqCst);
+		dump_process_table();
+	}
+}
+
+/*
+ * Type and state are printed last, because their names aren´t padded.
+ * Parents of processes without parent are printed as -1.
+ */
+pub fn dump_process_table() {
+	println!("{:>6} {:>6} {:>7} {:>6} {:>10} {:>10} TYPE STATE",
+		"PID", "PARENT", "THREADS", "FLAGS", "CPU MS", "MEMORY KB");
+	for info in &process_table() {
+		println!("{:>6} {:>6} {:>7} {:>6x} {:>10} {:>10} {:?} {:?}",
+			info.pid,
+			info.parent as i64,
+			info.threads,
+			info.flags,
+			info.cpu_time / NS_PER_MS,
+			info.mapped_memory / 0x400,
+			info.r#type,
+			info.state);
+	}
+}
@@ -4,8 +4,25 @@ use crate::{
 	std::log
 };
 use super::{
-	current_process
+	current_process,
+	current_thread,
+	exit_current_process
 };
+use super::signal::{
+	self,
+	SIGILL,
+	SIGTRAP,
+	SIGBUS,
+	SIGFPE,
+	SIGSEGV,
+	SIGNAL_EXIT_CODE
+};
+/*
+ * Set in the error code of page faults, which were caused
+ * by writing to a present page.
+ */
+const PAGE_FAULT_PRESENT: u64 = 1 << 0;
+const PAGE_FAULT_WRITE: u64 = 1 << 1;
 
 /*
  * Hint: These exceptions match the interrupt vectors.
@@ -41,22 +58,51 @@ enum Error {
 	SecurityException
 }
 
+impl Error {
+	/*
+	 * Signal, which the exception raises in the faulting process.
+	 */
+	fn signal(&self) -> u64 {
+		match self {
+			Error::DivisionByZero | Error::FloatingPointException | Error::SIMDFloatingPointException => SIGFPE,
+			Error::Debug | Error::Breakpoint => SIGTRAP,
+			Error::InvalidOpcode => SIGILL,
+			Error::AlignmentCheck => SIGBUS,
+			_ => SIGSEGV
+		}
+	}
+}
+
 /*
- * Logs state of crashed process and kills it
+ * Resolves write faults on copy on write mappings. Other faults in user
+ * mode enter the signal handler of the process, if it has one. Otherwise
+ * the state of the crashed process is logged and it´s killed.
  */
-pub fn handle_exception(vector: u8, frame: cpu::InterruptFrame, error: u64) {
+pub fn handle_exception(frame: &mut cpu::ExceptionFrame) {
+	let vector = frame.vector as u8;
+	if vector == Error::PageFault as u8 && frame.error & (PAGE_FAULT_PRESENT | PAGE_FAULT_WRITE) == PAGE_FAULT_PRESENT | PAGE_FAULT_WRITE &&
+		current_process().is_some_and(|process| process.lock().resolve_copy_on_write(std::cr2())) {
+		std::reset_cr2();
+		return;
+	}
+	let error = enum_iterator::all::<Error>().nth(vector as usize).expect("Unknown error.");
+	if frame.cs & 0x3 == 0x3 && signal::deliver_fault(frame, error.signal()) {
+		std::reset_cr2();
+		return;
+	}
 	let process = current_process().expect("Fatal exception in boot task.").lock();
 	log::error!("Crash! PID:        {}", process.pid);
+	log::error!("       TID:        {}", current_thread().expect("Fatal exception in boot task.").tid);
 	log::error!("       RIP:        0x{:x}", frame.rip);
 	log::error!("       RFLAGS:     0x{:x}", frame.rflags);
 	log::error!("       CR2:        0x{:x}", std::cr2());
-	log::error!("       Error       {:?}",
-		enum_iterator::all::<Error>().nth(vector as usize).expect("Unknown error.")
-	);
-	log::error!("       Error Code: 0x{:x}", error);
+	log::error!("       Error       {:?}", error);
+	log::error!("       Error Code: 0x{:x}", frame.error);
 	log::error!("       Flags:      {:?}", process.r#type);
 	std::reset_cr2();
-	std::exit();
+	// Killing requires the process lock.
+	drop(process);
+	exit_current_process(SIGNAL_EXIT_CODE + error.signal());
 }
 
 pub fn setup_exception_handlers() {
@@ -0,0 +1,379 @@
+/*
+ * Open files and the file descriptor tables of processes. A descriptor
+ * is an index into the table of its process, which refers to an open
+ * file. Open files keep the offset, so forked children share it with
+ * their parent like on POSIX systems. Besides files of a filesystem,
+ * the ends of pipes and shared memory are open files.
+ */
+
+use super::usercopy::{
+	copy_from_user,
+	copy_to_user
+};
+use super::pipe::Pipe;
+use super::sharedmemory::SharedMemory;
+use crate::virt::fs::{
+	self,
+	FilePath,
+	FSError
+};
+use crate::std::{
+	String,
+	Vec,
+	VecBase,
+	Mutex,
+	KernelError,
+	KernelResult
+};
+use abi::file::{
+	FileStat,
+	OPEN_READ,
+	OPEN_WRITE,
+	OPEN_CREATE,
+	OPEN_TRUNCATE,
+	SEEK_SET,
+	SEEK_CUR,
+	SEEK_END,
+	KIND_PIPE,
+	KIND_SHARED_MEMORY
+};
+use core::ops::Index;
+
+pub const MAX_FILES: usize = 64;
+// Writes are copied from user memory in chunks of this size.
+const WRITE_CHUNK_SIZE: usize = 0x200;
+
+#[derive(Clone)]
+enum FileObject {
+	File {
+		filesystem: usize,
+		path: FilePath
+	},
+	PipeReader(&'static Mutex<Pipe>),
+	PipeWriter(&'static Mutex<Pipe>),
+	SharedMemory(&'static Mutex<SharedMemory>)
+}
+
+pub struct OpenFile {
+	object: FileObject,
+	flags: u64,
+	offset: u64,
+	// Descriptors referring to the file. Slots without are reused.
+	references: usize
+}
+
+/*
+ * Open files are never moved, so descriptors refer to them directly.
+ */
+static OPEN_FILES: Mutex<Vec<Mutex<OpenFile>>> = Mutex::new(Vec::new());
+
+pub struct FileDescriptors {
+	files: [Option<&'static Mutex<OpenFile>>; MAX_FILES]
+}
+
+impl OpenFile {
+	/*
+	 * Opens the file at the absolute path. Directories can´t be opened.
+	 * Missing files are created with OPEN_CREATE, OPEN_TRUNCATE empties them.
+	 */
+	pub fn open(path: &String, flags: u64) -> KernelResult<&'static Mutex<OpenFile>> {
+		if flags & !(OPEN_READ | OPEN_WRITE | OPEN_CREATE | OPEN_TRUNCATE) != 0 ||
+			(flags & OPEN_TRUNCATE != 0 && flags & OPEN_WRITE == 0) {
+			return Err(KernelError::InvalidArgument);
+		}
+		let (filesystem, path) = fs::resolve(path)?;
+		{
+			let mut file_system = fs::filesystem(filesystem)?;
+			let info = match file_system.stat(path.clone()) {
+				Err(FSError::FileNotFound) if flags & OPEN_CREATE != 0 => {
+					file_system.create(path.clone(), false)?;
+					file_system.stat(path.clone())?
+				},
+				result => result?
+			};
+			if info.is_directory {
+				return Err(KernelError::IsDirectory);
+			}
+			if flags & OPEN_TRUNCATE != 0 {
+				file_system.truncate(path.clone(), 0)?;
+			}
+		}
+		Ok(OpenFile::add(FileObject::File { filesystem, path }, flags & (OPEN_READ | OPEN_WRITE)))
+	}
+
+	/*
+	 * Creates a pipe and returns its read and its write end.
+	 */
+	pub fn pipe() -> (&'static Mutex<OpenFile>, &'static Mutex<OpenFile>) {
+		let pipe = Pipe::new();
+		(
+			OpenFile::add(FileObject::PipeReader(pipe), OPEN_READ),
+			OpenFile::add(FileObject::PipeWriter(pipe), OPEN_WRITE)
+		)
+	}
+
+	/*
+	 * Creates shared memory and opens it for reading and writing.
+	 */
+	pub fn create_shared_memory(size: u64, name: Option<String>) -> KernelResult<&'static Mutex<OpenFile>> {
+		let memory = SharedMemory::create(size, name)?;
+		Ok(OpenFile::add(FileObject::SharedMemory(memory), OPEN_READ | OPEN_WRITE))
+	}
+
+	pub fn open_shared_memory(name: &str, flags: u64) -> KernelResult<&'static Mutex<OpenFile>> {
+		if flags & !(OPEN_READ | OPEN_WRITE) != 0 {
+			return Err(KernelError::InvalidArgument);
+		}
+		let memory = SharedMemory::open(name)?;
+		Ok(OpenFile::add(FileObject::SharedMemory(memory), flags))
+	}
+
+	fn add(object: FileObject, flags: u64) -> &'static Mutex<OpenFile> {
+		let file = OpenFile {
+			object,
+			flags,
+			offset: 0,
+			references: 1
+		};
+
+		let mut open_files = OPEN_FILES.lock();
+		// Slots are only reused with the table locked, so nobody else takes the free slot.
+		if let Some(slot) = OPEN_FILES.read().into_iter().find(|slot| slot.read().references == 0) {
+			*slot.lock() = file;
+			return slot;
+		}
+		open_files.push_back(Mutex::new(file));
+		OPEN_FILES.read().index(open_files.len() - 1)
+	}
+
+	pub fn stat(&self) -> KernelResult<FileStat> {
+		match &self.object {
+			FileObject::File { filesystem, path } => {
+				Ok(fs::filesystem(*filesystem)?.stat(path.clone())?.stat())
+			},
+			FileObject::PipeReader(pipe) | FileObject::PipeWriter(pipe) => Ok(FileStat {
+				size: pipe.lock().length() as u64,
+				kind: KIND_PIPE,
+				..FileStat::default()
+			}),
+			FileObject::SharedMemory(memory) => Ok(FileStat {
+				size: memory.lock().size() as u64,
+				kind: KIND_SHARED_MEMORY,
+				..FileStat::default()
+			})
+		}
+	}
+
+	/*
+	 * Reads up to the amount of bytes at the offset of a file to user memory.
+	 * Returns the amount of bytes read, which is zero at the end of the file.
+	 */
+	fn read_file_to_user(&mut self, filesystem: usize, path: &FilePath, address: u64, amount: usize) -> KernelResult<usize> {
+		let filesystem = fs::filesystem(filesystem)?;
+		let size = filesystem.stat(path.clone())?.size;
+		let amount = amount.min(size.saturating_sub(self.offset as usize));
+		if amount == 0 {
+			return Ok(0);
+		}
+		let data = filesystem.read(path.clone(), self.offset as usize, amount)?;
+		drop(filesystem);
+
+		let amount = amount.min(data.len());
+		let result = copy_to_user(address, &data.as_slice()[..amount]);
+		data.free();
+		result?;
+		self.offset += amount as u64;
+		Ok(amount)
+	}
+
+	/*
+	 * Writes the amount of bytes in user memory at the offset of a file.
+	 * Returns the amount of bytes written.
+	 */
+	fn write_file_from_user(&mut self, filesystem: usize, path: &FilePath, address: u64, amount: usize) -> KernelResult<usize> {
+		let mut chunk = [0u8; WRITE_CHUNK_SIZE];
+		let mut written = 0;
+		while written < amount {
+			let chunk_size = WRITE_CHUNK_SIZE.min(amount - written);
+			copy_from_user(&mut chunk[..chunk_size], address.checked_add(written as u64).ok_or(KernelError::BadAddress)?)?;
+			let chunk_written = fs::filesystem(filesystem)?.write(path.clone(), self.offset as usize, &chunk[..chunk_size])?;
+			self.offset += chunk_written as u64;
+			written += chunk_written;
+			if chunk_written < chunk_size {
+				break;
+			}
+		}
+		Ok(written)
+	}
+
+	/*
+	 * The shared memory of the file and the flags, it was opened with.
+	 * Other files can´t be mapped, so they return NoDevice.
+	 */
+	pub fn shared_memory(&self) -> KernelResult<(&'static Mutex<SharedMemory>, u64)> {
+		match self.object {
+			FileObject::SharedMemory(memory) => Ok((memory, self.flags)),
+			_ => Err(KernelError::NoDevice)
+		}
+	}
+
+	/*
+	 * Changes the size of the file. The offset stays, where it is.
+	 */
+	pub fn truncate(&self, size: u64) -> KernelResult<()> {
+		let FileObject::File { filesystem, path } = &self.object else {
+			return Err(KernelError::InvalidArgument);
+		};
+		if self.flags & OPEN_WRITE == 0 {
+			return Err(KernelError::BadFileDescriptor);
+		}
+		fs::filesystem(*filesystem)?.truncate(path.clone(), size as usize)?;
+		Ok(())
+	}
+
+	/*
+	 * Moves the offset relative to the origin and returns the new offset.
+	 */
+	pub fn seek(&mut self, offset: i64, origin: u64) -> KernelResult<u64> {
+		if !matches!(self.object, FileObject::File { .. }) {
+			return Err(KernelError::IllegalSeek);
+		}
+		let base = match origin {
+			SEEK_SET => 0,
+			SEEK_CUR => self.offset,
+			SEEK_END => self.stat()?.size,
+			_ => return Err(KernelError::InvalidArgument)
+		};
+		self.offset = base.checked_add_signed(offset).ok_or(KernelError::InvalidArgument)?;
+		Ok(self.offset)
+	}
+}
+
+unsafe impl Sync for OpenFile {}
+
+impl Mutex<OpenFile> {
+	/*
+	 * Reads up to the amount of bytes to user memory and returns the
+	 * amount of bytes read. Pipes are read without holding the lock,
+	 * because reading them may block.
+	 */
+	pub fn read_to_user(&'static self, address: u64, amount: usize) -> KernelResult<usize> {
+		let mut file = self.lock();
+		if file.flags & OPEN_READ == 0 {
+			return Err(KernelError::BadFileDescriptor);
+		}
+		match file.object.clone() {
+			FileObject::File { filesystem, path } => file.read_file_to_user(filesystem, &path, address, amount),
+			FileObject::PipeReader(pipe) => {
+				drop(file);
+				pipe.read_to_user(address, amount)
+			},
+			FileObject::PipeWriter(_) => Err(KernelError::BadFileDescriptor),
+			// Shared memory is only accessed through mappings.
+			FileObject::SharedMemory(_) => Err(KernelError::InvalidArgument)
+		}
+	}
+
+	/*
+	 * Writes the amount of bytes in user memory and returns the amount
+	 * of bytes written. Like reading, pipes are written without the lock.
+	 */
+	pub fn write_from_user(&'static self, address: u64, amount: usize) -> KernelResult<usize> {
+		let mut file = self.lock();
+		if file.flags & OPEN_WRITE == 0 {
+			return Err(KernelError::BadFileDescriptor);
+		}
+		match file.object.clone() {
+			FileObject::File { filesystem, path } => file.write_file_from_user(filesystem, &path, address, amount),
+			FileObject::PipeWriter(pipe) => {
+				drop(file);
+				pipe.write_from_user(address, amount)
+			},
+			FileObject::PipeReader(_) => Err(KernelError::BadFileDescriptor),
+			FileObject::SharedMemory(_) => Err(KernelError::InvalidArgument)
+		}
+	}
+
+	/*
+	 * Drops a reference to the file. Pipes notice, when the last reference
+	 * to an end is dropped. Shared memory loses the reference of the file.
+	 */
+	pub fn release(&self) {
+		let mut file = self.lock();
+		file.references -= 1;
+		if file.references == 0 {
+			match file.object {
+				FileObject::PipeReader(pipe) => pipe.close_reader(),
+				FileObject::PipeWriter(pipe) => pipe.close_writer(),
+				FileObject::SharedMemory(memory) => memory.release(),
+				FileObject::File { .. } => {}
+			}
+		}
+	}
+}
+
+impl FileDescriptors {
+	pub const fn new() -> FileDescriptors {
+		FileDescriptors {
+			files: [None; MAX_FILES]
+		}
+	}
+
+	/*
+	 * Adds the open file with the lowest free descriptor. The
+	 * file is closed, if the table is full.
+	 */
+	pub fn add(&mut self, file: &'static Mutex<OpenFile>) -> KernelResult<u64> {
+		let Some(fd) = self.files.iter().position(|slot| slot.is_none()) else {
+			file.release();
+			return Err(KernelError::TooManyFiles);
+		};
+		self.files[fd] = Some(file);
+		Ok(fd as u64)
+	}
+
+	/*
+	 * Makes the descriptor refer to the open file, which
+	 * another table refers to. The descriptor has to be free.
+	 */
+	pub fn inherit(&mut self, fd: u64, file: &'static Mutex<OpenFile>) {
+		assert!(self.files[fd as usize].is_none(), "Attempt to inherit a file to a used descriptor.");
+		file.lock().references += 1;
+		self.files[fd as usize] = Some(file);
+	}
+
+	pub fn get(&self, fd: u64) -> KernelResult<&'static Mutex<OpenFile>> {
+		self.files
+			.get(fd as usize)
+			.copied()
+			.flatten()
+			.ok_or(KernelError::BadFileDescriptor)
+	}
+
+	pub fn close(&mut self, fd: u64) -> KernelResult<()> {
+		let file = self.files
+			.get_mut(fd as usize)
+			.and_then(|slot| slot.take())
+			.ok_or(KernelError::BadFileDescriptor)?;
+		file.release();
+		Ok(())
+	}
+
+	pub fn close_all(&mut self) {
+		for file in self.files.iter_mut().filter_map(|slot| slot.take()) {
+			file.release();
+		}
+	}
+
+	/*
+	 * Copy of the table for a forked child. Both refer to the same open files.
+	 */
+	pub fn share(&self) -> FileDescriptors {
+		for file in self.files.iter().flatten() {
+			file.lock().references += 1;
+		}
+		FileDescriptors {
+			files: self.files
+		}
+	}
+}
@@ -91,6 +91,13 @@ impl Layer {
 	pub fn fill_global(&mut self, color: RGBColor) {
 		self.framebuffer.fill(color.into());
 	}
+	/*
+	 * Checks, whether the rectangle is completely inside the layer.
+	 */
+	pub fn contains_rect(&self, pos: (usize, usize), size: (usize, usize)) -> bool {
+		pos.0.checked_add(size.0).is_some_and(|end| end <= self.size.0) &&
+			pos.1.checked_add(size.1).is_some_and(|end| end <= self.size.1)
+	}
 	pub fn draw_rect(&mut self, pos: (usize, usize), size: (usize, usize), color: RGBColor) {
 		let raw_color: u32 = color.into();
 		for x in pos.0..pos.0 + size.0 {
@@ -1,6 +1,17 @@
 pub mod scheduler;
 pub mod graphicmanager;
 mod boottask;
+mod runqueue;
+mod thread;
+mod copyonwrite;
+mod signal;
+mod filedescriptor;
+mod pipe;
+mod sharedmemory;
+mod port;
+mod sleep;
+mod debugdump;
+mod usercopy;
 mod exception;
 mod syscall;
 
@@ -15,12 +26,30 @@ static INITALIZATION_LOCK: Lock = Lock::new_locked();
 pub use scheduler::{
 	r#yield,
 	exit_current_process,
+	exit_current_thread,
+	fork_current_process,
+	wait_for_child,
+	join_thread,
 	current_process,
+	current_thread,
+	process_table,
 	Process,
 	ProcessPrivilage,
+	ProcessPriority,
 	ProcessFlags
 };
 
+pub use signal::{
+	send_signal,
+	deliver_signals,
+	return_from_signal,
+	SignalAction
+};
+
+pub use sleep::sleep;
+
+pub use filedescriptor::FileDescriptors;
+
 pub use exception::{
 	setup_exception_handlers
 };
@@ -37,11 +66,14 @@ pub fn boot_core_setup() {
 }
 
 /*
- * Initalizes scheduler timer and exception handler
+ * Initalizes scheduler timer, TLB shootdowns, exception handler
+ * and the hotkey of the debug dump.
  */
 pub fn per_core_setup() {
 	scheduler::init_yield_timer();
+	crate::mm::connect_tlb_shootdown();
 	setup_exception_handlers();
+	debugdump::connect_hotkey();
 }
 
 pub fn is_initalized() -> bool {
@@ -49,25 +81,23 @@ pub fn is_initalized() -> bool {
 }
 
 /*
- * Tries to mounts every disk. Then the file "/init" in
- * each filesystem is searched and executed.
- * Panics if no executable or filesystem is found.
+ * Mounts the first disk holding a filesystem at the root "/" and
+ * executes the file "/init" of it. Init mounts the other disks.
+ * Panics if no filesystem or no executable is found.
  */
 pub fn spawn_init() -> ! {
 	crate::std::log::info!("Spawning init process ...");
-	let mut success = false;
 	let disk_ids = crate::hw::disk_ids();
 	assert!(!disk_ids.empty(), "No filesystems found to search init executable.");
-	for disk_id in &disk_ids {
-		let mounted = std::mount(*disk_id);
-		crate::std::log::info!("Mounted disk successfully: {}", disk_id);
-		if std::elf::load_elf_from_file(mounted, std::FilePath::new_unix("/init".into())) {
-			crate::std::log::info!("Found init executable at disk: {}", disk_id);
-			success = true;
-		}
-	}
+	let root = (&disk_ids).into_iter()
+		.find(|disk_id| std::mount(**disk_id, &"/".into()).is_ok())
+		.expect("No filesystem found to mount at the root.");
+	crate::std::log::info!("Mounted disk {} at the root.", root);
 
-	assert!(success, "No init executable found.");
+	let mut arguments = std::elf::ProgramArguments::new();
+	arguments.arguments.push_back("/init".into());
+	let result = std::elf::load_elf_from_file(&"/init".into(), &arguments, &FileDescriptors::new());
+	assert!(result.is_ok(), "No init executable found.");
 	log::info!("Init processes started successfully.");
 
 	std::exit()
@@ -0,0 +1,164 @@
+/*
+ * Pipes are ring buffers with a read and a write end. Both ends are
+ * open files, so processes use them through file descriptors.
+ * Readers block while the pipe is empty, writers while it´s full.
+ */
+
+use super::usercopy::{
+	copy_from_user,
+	copy_to_user
+};
+use super::scheduler::current_process;
+use super::signal::{
+	send_signal,
+	SIGPIPE
+};
+use crate::std::{
+	Vec,
+	VecBase,
+	Mutex,
+	WaitQueue,
+	KernelError,
+	KernelResult
+};
+use core::ops::Index;
+
+pub const PIPE_SIZE: usize = 0x1000;
+
+pub struct Pipe {
+	buffer: [u8; PIPE_SIZE],
+	// Position of the oldest byte in the buffer.
+	start: usize,
+	length: usize,
+	// Open read and write ends. Pipes without both are reused.
+	readers: usize,
+	writers: usize,
+	// Woken, whenever the content or the ends change.
+	events: WaitQueue
+}
+
+/*
+ * Pipes are never moved, so open files refer to them directly.
+ */
+static PIPES: Mutex<Vec<Mutex<Pipe>>> = Mutex::new(Vec::new());
+
+impl Pipe {
+	/*
+	 * Creates a pipe with one read and one write end.
+	 */
+	pub fn new() -> &'static Mutex<Pipe> {
+		let pipe = Pipe {
+			buffer: [0; PIPE_SIZE],
+			start: 0,
+			length: 0,
+			readers: 1,
+			writers: 1,
+			events: WaitQueue::new()
+		};
+
+		let mut pipes = PIPES.lock();
+		// Slots are only reused with the table locked, so nobody else takes the free slot.
+		if let Some(slot) = PIPES.read().into_iter().find(|slot| {
+			let slot = slot.read();
+			slot.readers == 0 && slot.writers == 0
+		}) {
+			*slot.lock() = pipe;
+			return slot;
+		}
+		pipes.push_back(Mutex::new(pipe));
+		PIPES.read().index(pipes.len() - 1)
+	}
+
+	pub fn length(&self) -> usize {
+		self.length
+	}
+}
+
+unsafe impl Sync for Pipe {}
+
+impl Mutex<Pipe> {
+	/*
+	 * Reads up to the amount of bytes to user memory. Blocks, until the
+	 * pipe isn´t empty. Returns zero, when all write ends are closed.
+	 */
+	pub fn read_to_user(&'static self, address: u64, amount: usize) -> KernelResult<usize> {
+		if amount == 0 {
+			return Ok(0);
+		}
+		loop {
+			{
+				let mut pipe = self.lock();
+				if pipe.length > 0 {
+					let amount = amount.min(pipe.length);
+					// The content may wrap around the end of the buffer.
+					let first = amount.min(PIPE_SIZE - pipe.start);
+					copy_to_user(address, &pipe.buffer[pipe.start..pipe.start + first])?;
+					copy_to_user(address + first as u64, &pipe.buffer[..amount - first])?;
+					pipe.start = (pipe.start + amount) % PIPE_SIZE;
+					pipe.length -= amount;
+					pipe.events.wake_all();
+					return Ok(amount);
+				}
+				if pipe.writers == 0 {
+					return Ok(0);
+				}
+			}
+			self.read().events.block_until(|| {
+				let pipe = self.read();
+				pipe.length > 0 || pipe.writers == 0
+			});
+		}
+	}
+
+	/*
+	 * Writes the amount of bytes in user memory. Blocks, until everything
+	 * is written. Writing without read ends raises SIGPIPE and fails with
+	 * BrokenPipe, unless some bytes were written already.
+	 */
+	pub fn write_from_user(&'static self, address: u64, amount: usize) -> KernelResult<usize> {
+		let mut written = 0;
+		while written < amount {
+			{
+				let mut pipe = self.lock();
+				if pipe.readers == 0 {
+					drop(pipe);
+					if written > 0 {
+						return Ok(written);
+					}
+					send_signal(current_process().expect("Syscall without process.").pid, SIGPIPE)?;
+					return Err(KernelError::BrokenPipe);
+				}
+				if pipe.length < PIPE_SIZE {
+					let chunk_size = (amount - written).min(PIPE_SIZE - pipe.length);
+					let end = (pipe.start + pipe.length) % PIPE_SIZE;
+					// The free space may wrap around the end of the buffer.
+					let first = chunk_size.min(PIPE_SIZE - end);
+					let source = address.checked_add(written as u64).ok_or(KernelError::BadAddress)?;
+					copy_from_user(&mut pipe.buffer[end..end + first], source)?;
+					copy_from_user(&mut pipe.buffer[..chunk_size - first], source + first as u64)?;
+					pipe.length += chunk_size;
+					written += chunk_size;
+					pipe.events.wake_all();
+					continue;
+				}
+			}
+			self.read().events.block_until(|| {
+				let pipe = self.read();
+				pipe.length < PIPE_SIZE || pipe.readers == 0
+			});
+		}
+		Ok(written)
+	}
+
+	pub fn close_reader(&self) {
+		let mut pipe = self.lock();
+		pipe.readers -= 1;
+		pipe.events.wake_all();
+	}
+
+	pub fn close_writer(&self) {
+		let mut pipe = self.lock();
+		pipe.writers -= 1;
+		pipe.events.wake_all();
+	}
+}
@@ -0,0 +1,348 @@
+/*
+ * Ports pass messages between processes. The process, which created
+ * a port, receives the messages other processes send to it. Calls block
+ * the sender, until the owner replies. Messages and replies are copied
+ * through kernel buffers, because syscalls only reach the memory of
+ * the current process.
+ */
+
+use super::usercopy::{
+	copy_from_user,
+	copy_to_user,
+	write_to_user
+};
+use super::scheduler::current_process;
+use crate::std::{
+	Box,
+	String,
+	Vec,
+	VecBase,
+	Mutex,
+	WaitQueue,
+	KernelError,
+	KernelResult
+};
+use abi::ipc::{
+	MessageInfo,
+	MAX_MESSAGE_SIZE,
+	MAX_QUEUED_MESSAGES,
+	NO_REPLY
+};
+use core::sync::atomic::{
+	AtomicU64,
+	Ordering
+};
+
+/*
+ * Copy of a message or a reply in kernel memory.
+ */
+struct Buffer {
+	content: Box<[u8]>,
+	length: usize
+}
+
+struct Message {
+	// NO_REPLY, unless the sender waits for a reply.
+	id: u64,
+	sender: u64,
+	buffer: Buffer
+}
+
+/*
+ * Call, whose sender waits for the reply.
+ */
+struct PendingCall {
+	id: u64,
+	reply: Option<Buffer>
+}
+
+pub struct Port {
+	id: u64,
+	name: String,
+	owner: u64,
+	destroyed: bool,
+	messages: Vec<Message>,
+	calls: Vec<PendingCall>,
+	// Woken, whenever messages, replies or the state change.
+	events: WaitQueue
+}
+
+/*
+ * Ports are never moved, so blocked senders refer to them directly.
+ * Slots of destroyed ports are reused, once no sender waits anymore.
+ */
+static PORTS: Mutex<Vec<Mutex<Port>>> = Mutex::new(Vec::new());
+// Ids of ports and calls. They´re never reused, so stale ids are NotFound.
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+impl Buffer {
+	fn from_user(address: u64, length: usize) -> KernelResult<Buffer> {
+		if length > MAX_MESSAGE_SIZE as usize {
+			return Err(KernelError::InvalidArgument);
+		}
+		let mut content: Box<[u8]> = Box::try_new_sized(length.max(1)).ok_or(KernelError::OutOfMemory)?;
+		if let Err(error) = copy_from_user(&mut content.as_slice_mut()[..length], address) {
+			content.free();
+			return Err(error);
+		}
+		Ok(Buffer {
+			content,
+			length
+		})
+	}
+
+	/*
+	 * Copies as much as fits into the capacity to user memory
+	 * and returns the amount of bytes copied.
+	 */
+	fn copy_to_user(&self, address: u64, capacity: usize) -> KernelResult<usize> {
+		let amount = self.length.min(capacity);
+		copy_to_user(address, &self.content.as_slice()[..amount])?;
+		Ok(amount)
+	}
+
+	fn free(self) {
+		self.content.free();
+	}
+}
+
+impl Port {
+	/*
+	 * Creates a port owned by the current process and returns its id.
+	 */
+	pub fn create(name: String) -> KernelResult<u64> {
+		if name.is_empty() {
+			return Err(KernelError::InvalidArgument);
+		}
+		let owner = current_pid();
+
+		let mut ports = PORTS.lock();
+		if Port::find(name.as_str()).is_some() {
+			return Err(KernelError::AlreadyExists);
+		}
+		let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+		let port = Port {
+			id,
+			name,
+			owner,
+			destroyed: false,
+			messages: Vec::new(),
+			calls: Vec::new(),
+			events: WaitQueue::new()
+		};
+
+		// Slots are only reused with the table locked, so nobody else takes the free slot.
+		if let Some(slot) = PORTS.read().into_iter().find(|slot| {
+			let slot = slot.read();
+			slot.destroyed && slot.calls.empty()
+		}) {
+			*slot.lock() = port;
+			return Ok(id);
+		}
+		ports.push_back(Mutex::new(port));
+		Ok(id)
+	}
+
+	pub fn lookup(name: &str) -> KernelResult<u64> {
+		let _ports = PORTS.lock();
+		Port::find(name).map(|port| port.read().id).ok_or(KernelError::NotFound)
+	}
+
+	/*
+	 * Requires the table to be locked.
+	 */
+	fn find(name: &str) -> Option<&'static Mutex<Port>> {
+		PORTS.read().into_iter().find(|slot| {
+			let slot = slot.read();
+			!slot.destroyed && slot.name.as_str() == name
+		})
+	}
+
+	fn by_id(id: u64) -> KernelResult<&'static Mutex<Port>> {
+		let _ports = PORTS.lock();
+		PORTS.read().into_iter().find(|slot| {
+			let slot = slot.read();
+			!slot.destroyed && slot.id == id
+		}).ok_or(KernelError::NotFound)
+	}
+
+	/*
+	 * Returns the port, if the current process owns it.
+	 */
+	fn owned(id: u64) -> KernelResult<&'static Mutex<Port>> {
+		let port = Port::by_id(id)?;
+		if port.lock().owner != current_pid() {
+			return Err(KernelError::NotPermitted);
+		}
+		Ok(port)
+	}
+
+	/*
+	 * Queued messages are dropped, waiting senders fail.
+	 */
+	fn destroy(&mut self) {
+		self.destroyed = true;
+		while !self.messages.empty() {
+			self.messages.remove(0).buffer.free();
+		}
+		self.events.wake_all();
+	}
+
+	/*
+	 * Destroys the port, if the current process owns it.
+	 */
+	pub fn destroy_owned(id: u64) -> KernelResult<()> {
+		Port::owned(id)?.lock().destroy();
+		Ok(())
+	}
+
+	/*
+	 * Destroys every port of the process, when it exits.
+	 */
+	pub fn destroy_all(pid: u64) {
+		let _ports = PORTS.lock();
+		for port in PORTS.read().into_iter() {
+			let mut port = port.lock();
+			if !port.destroyed && port.owner == pid {
+				port.destroy();
+			}
+		}
+	}
+
+	/*
+	 * Queues a message without waiting for a reply.
+	 */
+	pub fn send(id: u64, address: u64, length: usize) -> KernelResult<()> {
+		let port = Port::by_id(id)?;
+		let buffer = Buffer::from_user(address, length)?;
+		port.queue(id, Message {
+			id: NO_REPLY,
+			sender: current_pid(),
+			buffer
+		})
+	}
+
+	/*
+	 * Sends a message and blocks, until the owner replies. The reply is
+	 * copied to user memory. Returns the amount of bytes copied.
+	 */
+	pub fn call(id: u64, address: u64, length: usize, reply_address: u64, capacity: usize) -> KernelResult<usize> {
+		let port = Port::by_id(id)?;
+		let buffer = Buffer::from_user(address, length)?;
+		let call_id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+		port.queue(id, Message {
+			id: call_id,
+			sender: current_pid(),
+			buffer
+		})?;
+
+		// The pending call keeps the slot from being reused meanwhile.
+		port.read().events.block_until(|| {
+			let port = port.read();
+			port.destroyed || (&port.calls).into_iter().any(|call| call.id == call_id && call.reply.is_some())
+		});
+		let call = {
+			let mut port = port.lock();
+			let idx = (&port.calls).into_iter()
+				.position(|call| call.id == call_id)
+				.expect("Pending call vanished.");
+			port.calls.remove(idx)
+		};
+		let reply = call.reply.ok_or(KernelError::BrokenPipe)?;
+		let result = reply.copy_to_user(reply_address, capacity);
+		reply.free();
+		result
+	}
+
+	/*
+	 * Blocks, until a message is queued, and copies it to user memory.
+	 * Messages stay queued, if they can´t be copied. Returns the amount
+	 * of bytes copied.
+	 */
+	pub fn receive(id: u64, address: u64, capacity: usize, info_address: u64) -> KernelResult<usize> {
+		let port = Port::owned(id)?;
+		loop {
+			{
+				let mut port = port.lock();
+				if port.destroyed || port.id != id {
+					return Err(KernelError::NotFound);
+				}
+				if !port.messages.empty() {
+					let message = &port.messages[0];
+					let amount = message.buffer.copy_to_user(address, capacity)?;
+					if info_address != 0x0 {
+						write_to_user(info_address, &MessageInfo {
+							id: message.id,
+							sender: message.sender,
+							length: message.buffer.length as u64
+						})?;
+					}
+					port.messages.remove(0).buffer.free();
+					port.events.wake_all();
+					return Ok(amount);
+				}
+			}
+			port.read().events.block_until(|| {
+				let port = port.read();
+				port.destroyed || port.id != id || !port.messages.empty()
+			});
+		}
+	}
+
+	/*
+	 * Passes the reply to the sender of the call.
+	 */
+	pub fn reply(id: u64, call_id: u64, address: u64, length: usize) -> KernelResult<()> {
+		let port = Port::owned(id)?;
+		let buffer = Buffer::from_user(address, length)?;
+		let mut port = port.lock();
+		let Some(call) = (&mut port.calls).into_iter().find(|call| call.id == call_id && call.reply.is_none()) else {
+			drop(port);
+			buffer.free();
+			return Err(KernelError::NotFound);
+		};
+		call.reply = Some(buffer);
+		port.events.wake_all();
+		Ok(())
+	}
+}
+
+unsafe impl Sync for Port {}
+
+impl Mutex<Port> {
+	/*
+	 * Queues the message. Blocks, while the queue is full. Calls
+	 * are listed as pending, until their sender takes the reply.
+	 */
+	fn queue(&'static self, id: u64, message: Message) -> KernelResult<()> {
+		loop {
+			{
+				let mut port = self.lock();
+				if port.destroyed || port.id != id {
+					drop(port);
+					message.buffer.free();
+					return Err(KernelError::BrokenPipe);
+				}
+				if port.messages.len() < MAX_QUEUED_MESSAGES {
+					if message.id != NO_REPLY {
+						port.calls.push_back(PendingCall {
+							id: message.id,
+							reply: None
+						});
+					}
+					port.messages.push_back(message);
+					port.events.wake_all();
+					return Ok(());
+				}
+			}
+			self.read().events.block_until(|| {
+				let port = self.read();
+				port.destroyed || port.id != id || port.messages.len() < MAX_QUEUED_MESSAGES
+			});
+		}
+	}
+}
+
+fn current_pid() -> u64 {
+	current_process().expect("Syscall without process.").lock().pid
+}
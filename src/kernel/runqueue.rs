@@ -0,0 +1,82 @@
+/*
+ * Run queues of the scheduler. Every core has its own run queues.
+ * Every priority has its own queue of idle threads, which is
+ * served round robin.
+ */
+
+use super::scheduler::ProcessPriority;
+use super::thread::Thread;
+use crate::std::{
+	Mutex,
+	Vec,
+	VecBase
+};
+use enum_iterator::Sequence;
+
+const PRIORITY_AMOUNT: usize = ProcessPriority::CARDINALITY;
+/*
+ * Amount of picks, a non empty queue may be passed over by
+ * higher priorities, before it´s served anyway.
+ */
+const STARVATION_LIMIT: usize = 16;
+
+pub struct RunQueues {
+	queues: [Vec<&'static Mutex<Thread>>; PRIORITY_AMOUNT],
+	starvation: [usize; PRIORITY_AMOUNT]
+}
+
+impl RunQueues {
+	pub const fn new() -> RunQueues {
+		RunQueues {
+			queues: [const { Vec::new() }; PRIORITY_AMOUNT],
+			starvation: [0; PRIORITY_AMOUNT]
+		}
+	}
+
+	/*
+	 * Appends the thread to the queue of the given priority.
+	 */
+	pub fn push(&mut self, thread: &'static Mutex<Thread>, priority: ProcessPriority) {
+		self.queues[priority as usize].push_back(thread);
+	}
+
+	/*
+	 * Takes the first thread of the highest priority queue.
+	 * Queues, which were passed over STARVATION_LIMIT times,
+	 * are served first, beginning with the lowest priority.
+	 */
+	pub fn pop(&mut self) -> Option<&'static Mutex<Thread>> {
+		let priority = self.next_priority()?;
+		for (idx, starvation) in self.starvation.iter_mut().enumerate() {
+			if idx == priority || self.queues[idx].empty() {
+				*starvation = 0;
+			} else if idx > priority {
+				*starvation += 1;
+			}
+		}
+		Some(self.queues[priority].remove(0))
+	}
+
+	/*
+	 * Removes the first thread matching the predicate from the queues.
+	 */
+	pub fn remove_first<F: Fn(&'static Mutex<Thread>) -> bool>(&mut self, predicate: F) -> Option<&'static Mutex<Thread>> {
+		for queue in &mut self.queues {
+			if let Some(idx) = (&*queue).into_iter().position(|thread| predicate(thread)) {
+				return Some(queue.remove(idx));
+			}
+		}
+		None
+	}
+
+	pub fn len(&self) -> usize {
+		self.queues.iter().map(|queue| queue.len()).sum()
+	}
+
+	fn next_priority(&self) -> Option<usize> {
+		(0..PRIORITY_AMOUNT)
+			.rev()
+			.find(|idx| self.starvation[*idx] >= STARVATION_LIMIT && !self.queues[*idx].empty())
+			.or_else(|| (0..PRIORITY_AMOUNT).find(|idx| !self.queues[*idx].empty()))
+	}
+}
@@ -1,5 +1,5 @@
 /*
- * TODO: Seperate process, task state and remainders of the scheduler
+ * TODO: Seperate process and task state from the remainders of the scheduler
  * TODO: Docs
  */
 
@@ -11,24 +11,56 @@ use crate::{
 };
 use crate::std::{
 	PerCpu,
+	MAX_CORES,
 	Mutex,
 	Vec,
 	VecBase,
-	RAMAllocator,
-	Allocator,
-	hltloop,
 	Box,
+	String,
 	cli,
-	sti
+	sti,
+	interrupts_enabled,
+	current_core,
+	KernelError,
+	KernelResult
 };
 use crate::hw::{
-	cpu
+	cpu,
+	cpu::syscall::SyscallFrame,
+	clock
+};
+use super::runqueue::RunQueues;
+use super::copyonwrite;
+use super::signal::Signals;
+use super::filedescriptor::FileDescriptors;
+use super::usercopy::USER_ADDRESS_LIMIT;
+use super::port::Port;
+use super::sharedmemory::SharedMemory;
+use super::thread::{
+	Thread,
+	ThreadState,
+	EXITED_THREADS,
+	threads
 };
 use core::{
 	arch::asm,
-	ops::Index
+	ops::Index,
+	sync::atomic::{
+		AtomicBool,
+		AtomicU64,
+		AtomicUsize,
+		Ordering
+	}
+};
+use cpu::{
+	GDT,
+	LAPIC
+};
+use abi::memory::{
+	PROT_READ,
+	PROT_WRITE,
+	PROT_EXEC
 };
-use cpu::GDT;
 
 #[derive(PartialEq)]
 pub enum ProcessPrivilage {
@@ -36,6 +68,7 @@ pub enum ProcessPrivilage {
 	USER
 }
 
+// The order matches abi::process::TYPE_*.
 #[derive(Clone, Copy, PartialEq)]
 #[derive(Debug)]
 pub enum ProcessType {
@@ -44,15 +77,39 @@ pub enum ProcessType {
 	NORMAL
 }
 
+/*
+ * A process is KILLED, after exit was called, and stays, until
+ * it´s reaped. It´s a zombie, when none of its threads is alive.
+ * REAPED slots are reused for new processes.
+ * The order matches abi::process::STATE_*.
+ */
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum ProcessState {
 	RUNNING,
-	IDLE,
-	KILLED
+	KILLED,
+	REAPED
+}
+
+/*
+ * Scheduling classes. Lower classes only run, when no thread
+ * of a higher class is idle or they starved for too long.
+ * The order matches abi::process::PRIORITY_*.
+ */
+#[derive(Clone, Copy, PartialEq, Debug, enum_iterator::Sequence)]
+pub enum ProcessPriority {
+	REALTIME,
+	NORMAL,
+	IDLE
 }
 
+/*
+ * Privileged processes may mount filesystems, run realtime threads
+ * and signal every user process. Only programs started by the kernel,
+ * like /init, are privileged. Forks and programs they start aren´t.
+ */
 pub enum ProcessFlags {
-	GraphicManager = 1 << 0
+	GraphicManager = 1 << 0,
+	Privileged = 1 << 1
 }
 
 #[derive(Clone, Copy)]
@@ -67,38 +124,124 @@ pub struct TaskState {
 	pub uid: u64
 }
 
+/*
+ * Content of copy on write mappings is shared with
+ * other processes and mapped read only. The content of
+ * shared memory mappings belongs to the shared memory.
+ */
 pub struct ProcessMapping {
 	virt_addr: u64,
 	content: Box<[u8]>,
-	flags: u64
+	flags: u64,
+	// Whether the process may write, copy on write content is copied first.
+	writable: bool,
+	copy_on_write: bool,
+	shared_memory: Option<&'static Mutex<SharedMemory>>
 }
 
+/*
+ * Address space and resources shared by the threads of a program.
+ * task_state, stack and priority describe the main thread,
+ * until the process is spawned.
+ */
 pub struct Process {
 	pub mappings: Vec<ProcessMapping>,
 	pub mapping_pages: Vec<ProcessMapping>,
 	pub page_table: Box<Mutex<PageTable>>,
 	task_state: TaskState,
+	stack: Box<[u8]>,
 	pub r#type: ProcessType,
 	state: ProcessState,
+	priority: ProcessPriority,
+	// Spawned threads, which didn´t become zombies yet.
+	pub(super) live_threads: AtomicUsize,
+	// Bit n allows the threads to run on the core with the LAPIC id n.
+	affinity: u64,
+	pub(super) signals: Signals,
+	pub(super) files: FileDescriptors,
+	// Absolute path, which relative paths start at.
+	pub working_directory: String,
 	pub flags: u64,
+	exit_code: u64,
+	// Nanoseconds, which the threads ran.
+	cpu_time: AtomicU64,
+
+	pub pid: u64,
+	pub parent: u64
+}
 
-	pub pid: u64
+/*
+ * Entry of the process table, as it´s listed by
+ * the process list syscall and the debug dump.
+ */
+#[derive(Clone, Copy)]
+pub struct ProcessInfo {
+	pub pid: u64,
+	pub parent: u64,
+	pub r#type: ProcessType,
+	pub state: ProcessState,
+	pub flags: u64,
+	pub threads: u64,
+	// In nanoseconds.
+	pub cpu_time: u64,
+	// Bytes of the mappings.
+	pub mapped_memory: u64
 }
 
 pub trait RipCast {
 	fn ripcast(&self) -> u64;
 }
 
-static STATE_PER_CPU: PerCpu<TaskState> = PerCpu::new(TaskState::INVALID);
-static PID_PER_CPU: PerCpu<u64> = PerCpu::new(u64::MAX);
+static TID_PER_CPU: PerCpu<u64> = PerCpu::new(u64::MAX);
+/*
+ * Thread, that was switched out last on the core. It´s marked
+ * as idle after the switch completed, so no other core picks it
+ * up while its registers are still being saved.
+ */
+static PREVIOUS_TID_PER_CPU: PerCpu<u64> = PerCpu::new(u64::MAX);
+/*
+ * Saved stack of the init process of a core. It runs, when no
+ * thread is runnable.
+ */
+static IDLE_STACK_POINTER: PerCpu<u64> = PerCpu::new(0x0);
+// Monotonic time, when the CPU time of the core was accounted last.
+static LAST_ACCOUNTING: PerCpu<u64> = PerCpu::new(0x0);
 static PROCESSES: Mutex<Vec<Mutex<Process>>> = Mutex::new(Vec::new());
 static UID_COUNTER: Mutex<u64> = Mutex::new(0x0);
-static NEXT_PROCESS: Mutex<usize> = Mutex::new(0);
+/*
+ * Run queues of every core. They´re only locked with
+ * interrupts disabled, so the timer never interrupts a holder.
+ */
+static RUN_QUEUES: PerCpu<Mutex<RunQueues>> = PerCpu::from_array(
+	[const { Mutex::new(RunQueues::new()) }; MAX_CORES]
+);
+// Set for every core, which runs the scheduler.
+static SCHEDULING_CORES: PerCpu<bool> = PerCpu::new(false);
+/*
+ * Scheduling decisions of a core. Every BALANCE_INTERVAL
+ * decisions, a thread of the busiest core is taken over.
+ */
+static SCHEDULE_COUNTER: PerCpu<usize> = PerCpu::new(0);
+const BALANCE_INTERVAL: usize = 8;
+/*
+ * Incremented with every change of an affinity. Cores, which didn´t see the
+ * current generation yet, move threads, that mustn´t run on them anymore.
+ */
+static AFFINITY_GENERATION: AtomicU64 = AtomicU64::new(0);
+static SEEN_AFFINITY_GENERATION: PerCpu<u64> = PerCpu::new(0);
+// Set, when a blocked thread has to be queued again.
+static PENDING_WAKEUPS: AtomicBool = AtomicBool::new(false);
+static TIME_SLICE: Mutex<u64> = Mutex::new(10); // In milliseconds
+// Mappings without an address chosen by the process are placed from here on.
+const MAPPING_ADDRESS: u64 = 0x600000000000;
+// Page entry flags of user mappings.
+const USER_FLAG: u64 = 0x4;
+const NO_EXECUTE_FLAG: u64 = 0x8000000000000000;
 
 #[link(name="switcher")]
 unsafe extern "sysv64" {
 	fn jump_state(state: &TaskState) -> !;
-	fn load_state(state: &TaskState, to_safe: &TaskState) -> !;
+	fn switch_context(to_save: *mut u64, to_load: u64);
 }
 
 impl RipCast for u64 {
@@ -124,7 +267,11 @@ impl TaskState {
 		uid: 0xffffffffffffffff
 	};
 	pub fn new<R: RipCast>(s_base: u16, rsp: u64, rip: R) -> Option<TaskState> {
-		*UID_COUNTER.lock() += 1;
+		let uid = {
+			let mut uid_counter = UID_COUNTER.lock();
+			*uid_counter += 1;
+			*uid_counter
+		};
 		Some(TaskState {
 			registers: [
 				0x0, // RAX
@@ -149,22 +296,15 @@ impl TaskState {
 			cs: s_base as u64,
 			ds: (s_base + 0x8) as u64,
 			gs: 0x0,
-			uid: *UID_COUNTER.lock()
+			uid
 		})
 	}
 
 	pub fn jump(&self) -> ! {
-		*STATE_PER_CPU.deref_mut() = *self;
-
 		call_asm!(jump_state, self);
 
 		panic!("Jumping failed.");
 	}
-	pub fn load(&self) {
-		let last_state = &mut STATE_PER_CPU.deref_mut();
-		*STATE_PER_CPU.deref_mut() = *self;
-		call_asm!(load_state, self, last_state)
-	}
 }
 
 impl PartialEq for TaskState {
@@ -173,18 +313,130 @@ impl PartialEq for TaskState {
 	}
 }
 
+impl ProcessMapping {
+	fn contains(&self, address: u64) -> bool {
+		address >= self.virt_addr && address < self.virt_addr + self.content.alloc_len() as u64
+	}
+
+	fn overlaps(&self, address: u64, size: usize) -> bool {
+		address < self.virt_addr + self.content.alloc_len() as u64 && self.virt_addr < address + size as u64
+	}
+
+	/*
+	 * Maps the content copy on write into the target table
+	 * and returns the mapping for it. Both become read only.
+	 * Shared memory stays shared and keeps its permissions.
+	 */
+	fn share(&mut self, page_table: &Mutex<PageTable>, target_table: &Mutex<PageTable>) -> ProcessMapping {
+		if let Some(memory) = self.shared_memory {
+			memory.acquire();
+			let mapping = ProcessMapping {
+				virt_addr: self.virt_addr,
+				content: memory.lock().content(),
+				flags: self.flags,
+				writable: self.writable,
+				copy_on_write: false,
+				shared_memory: Some(memory)
+			};
+			mapping.map(target_table);
+			return mapping;
+		}
+		copyonwrite::share(self.content.physical_address());
+		self.copy_on_write = true;
+		page_table.lock().set_writable(self.virt_addr, self.content.alloc_len(), false);
+		// Other threads of the process mustn´t write to the shared content anymore.
+		crate::mm::shootdown_tlb(page_table);
+
+		let mapping = ProcessMapping {
+			virt_addr: self.virt_addr,
+			content: unsafe {
+				self.content.alias()
+			},
+			flags: self.flags,
+			writable: self.writable,
+			copy_on_write: true,
+			shared_memory: None
+		};
+		mapping.map(target_table);
+		mapping
+	}
+
+	/*
+	 * Maps the content page by page. Copy on write content stays read only.
+	 */
+	fn map(&self, page_table: &Mutex<PageTable>) {
+		let mut page_table = page_table.lock();
+		for offset in (0..self.content.alloc_len() as u64).step_by(0x1000) {
+			page_table.map(self.virt_addr + offset, self.content.physical_address() + offset, 0x1000, self.flags);
+			page_table.set_writable(self.virt_addr + offset, 0x1000, self.writable && !self.copy_on_write);
+		}
+	}
+
+	fn unmap(&self, page_table: &Mutex<PageTable>) {
+		let mut page_table = page_table.lock();
+		for offset in (0..self.content.alloc_len() as u64).step_by(0x1000) {
+			page_table.unmap(self.virt_addr + offset, 0x1000);
+		}
+	}
+
+	/*
+	 * Makes a copy on write mapping writable after a write fault.
+	 * The content is copied, unless no other process uses it anymore.
+	 */
+	fn resolve_copy_on_write(&mut self, page_table: &Mutex<PageTable>) {
+		let released = copyonwrite::release(self.content.physical_address());
+		self.copy_on_write = false;
+		if released {
+			page_table.lock().set_writable(self.virt_addr, self.content.alloc_len(), self.writable);
+		} else {
+			let mut content = Box::<[u8]>::new_sized(self.content.alloc_len());
+			content.as_slice_mut().copy_from_slice(self.content.as_slice());
+			self.content = content;
+			// Page by page with the writable bit, the flags may lack it.
+			self.map(page_table);
+			// Other threads of the process mustn´t use the shared content anymore.
+			crate::mm::shootdown_tlb(page_table);
+		}
+	}
+
+	/*
+	 * Frees the content, if no other process shares it.
+	 */
+	fn free(&mut self) {
+		let content = core::mem::replace(&mut self.content, Box::null());
+		if let Some(memory) = self.shared_memory.take() {
+			memory.release();
+		} else if !self.copy_on_write || copyonwrite::release(content.physical_address()) {
+			content.free();
+		}
+		self.copy_on_write = false;
+	}
+}
+
 impl Process {
+	/*
+	 * The pid is the uid of the task state, so it´s the tid of the main thread.
+	 */
 	pub fn new<EntryAddr: RipCast>(privilage: ProcessPrivilage, entry_addr: EntryAddr) -> Option<Process> {
-		*UID_COUNTER.lock() += 1;
 		let mut process = Process {
 			mappings: Vec::new(),
 			mapping_pages: Vec::new(),
 			page_table: PageTable::new_boxed(),
 			task_state: TaskState::INVALID,
+			stack: Box::null(),
 			r#type: ProcessType::NORMAL,
-			state: ProcessState::IDLE,
+			state: ProcessState::RUNNING,
+			priority: ProcessPriority::NORMAL,
+			live_threads: AtomicUsize::new(0),
+			affinity: u64::MAX,
+			signals: Signals::new(),
+			files: FileDescriptors::new(),
+			working_directory: String::from("/"),
 			flags: 0,
-			pid: *UID_COUNTER.lock()
+			exit_code: 0,
+			cpu_time: AtomicU64::new(0),
+			pid: 0,
+			parent: current_process().map_or(u64::MAX, |parent| parent.pid)
 		};
 		process.page_table.lock().init();
 		process.task_state = TaskState::new(
@@ -192,11 +444,13 @@ impl Process {
 			0x0,
 			entry_addr
 		)?;
+		process.pid = process.task_state.uid;
 		Some(process)
 	}
 	pub fn new_with_stack<EntryAddr: RipCast>(privilage: ProcessPrivilage, entry_addr: EntryAddr, stack_size: usize) -> Option<Process> {
 		let mut process = Process::new(privilage, entry_addr)?;
-		process.assign_stack(RAMAllocator::default().allocate::<u8>(stack_size)? as u64 + stack_size as u64);
+		process.stack = Box::new_sized(stack_size);
+		process.assign_stack(process.stack.as_stack() as u64);
 		Some(process)
 	}
 	pub fn spawn_init_process<EntryAddr: RipCast>(entry_addr: EntryAddr) -> ! {
@@ -209,14 +463,18 @@ impl Process {
 	pub fn spawn_with_stack<EntryAddr: RipCast>(privilage: ProcessPrivilage, entry_addr: EntryAddr) -> Option<u64> {
 		let process = Self::new_with_stack(privilage, entry_addr, 0x50000)?;
 		let pid = process.pid;
-		PROCESSES.lock().push_back(Mutex::new(process));
+		process.spawn();
 		Some(pid)
 	}
 	pub fn from_pid(pid: u64) -> Option<&'static Mutex<Process>> {
 		let processes = PROCESSES.read();
 		Some(
 			PROCESSES.read().index(
-				processes.into_iter().position(|p| p.lock().pid == pid)?
+				// Not locking, the scheduler looks up processes while others hold their locks.
+				processes.into_iter().position(|p| {
+					let process = p.read();
+					process.pid == pid && process.state != ProcessState::REAPED
+				})?
 			)
 		)
 	}
@@ -229,13 +487,16 @@ impl Process {
 	pub fn assign_stack(&mut self, stack: u64) {
 		self.task_state.registers[7] = stack;
 	}
+	pub fn assign_files(&mut self, files: FileDescriptors) {
+		self.files.close_all();
+		self.files = files;
+	}
 	pub fn disable_interrupts(&mut self) -> &mut Self {
 		self.task_state.rflags &= !0x200;
 		self
 	}
 	fn switch_init(&mut self) -> ! {
-		self.state = ProcessState::RUNNING;
-		PID_PER_CPU.set(self.pid);
+		TID_PER_CPU.set(u64::MAX);
 		self.task_state.jump()
 	}
 
@@ -244,8 +505,139 @@ impl Process {
 		self
 	}
 
-	pub fn spawn(self) {
-		PROCESSES.lock().push_back(Mutex::new(self));
+	/*
+	 * Adds the process to the scheduler and starts its main thread.
+	 * The slot of a reaped process is reused, if there´s one.
+	 */
+	pub fn spawn(mut self) {
+		reap_orphans();
+
+		let task_state = self.task_state;
+		let stack = core::mem::replace(&mut self.stack, Box::null());
+		let priority = self.priority;
+		// Counted before, so the process is never seen as zombie.
+		self.live_threads = AtomicUsize::new(1);
+		let process = {
+			let mut processes = PROCESSES.lock();
+			if let Some(slot) = PROCESSES.read().into_iter().find(|slot| slot.lock().state == ProcessState::REAPED) {
+				*slot.lock() = self;
+				slot
+			} else {
+				processes.push_back(Mutex::new(self));
+				PROCESSES.read().index(processes.len() - 1)
+			}
+		};
+		Thread::new(process, task_state, stack, priority).spawn();
+	}
+
+	fn is_alive(&self) -> bool {
+		self.state == ProcessState::RUNNING && !self.is_zombie()
+	}
+
+	fn is_zombie(&self) -> bool {
+		self.state != ProcessState::REAPED && self.live_threads.load(Ordering::SeqCst) == 0
+	}
+
+	/*
+	 * Frees the threads, mappings, files, ports and the page table of a zombie.
+	 * Only the exit code and pid remain, until the slot is reused.
+	 */
+	fn reap(&mut self) {
+		assert!(self.is_zombie(), "Attempt to reap process, which didn´t exit.");
+		for thread in threads() {
+			let mut thread = thread.lock();
+			if thread.state == ThreadState::ZOMBIE && thread.process.pid == self.pid {
+				thread.reap();
+			}
+		}
+		self.free_resources();
+		self.state = ProcessState::REAPED;
+	}
+
+	/*
+	 * Frees a process, which was never spawned, e.g. after
+	 * loading its program failed.
+	 */
+	pub fn free(mut self) {
+		self.free_resources();
+	}
+
+	fn free_resources(&mut self) {
+		for mapping in (&mut self.mappings).into_iter().chain(&mut self.mapping_pages) {
+			mapping.free();
+		}
+		self.mappings.clear();
+		self.mapping_pages.clear();
+		self.files.close_all();
+		Port::destroy_all(self.pid);
+
+		core::mem::replace(&mut self.stack, Box::null()).free();
+
+		self.page_table.lock().free_directories();
+		core::mem::replace(&mut self.page_table, Box::null()).free();
+	}
+
+	pub fn priority(&self) -> ProcessPriority {
+		self.priority
+	}
+
+	// Kernel tasks run in ring 0, user processes can´t signal them.
+	pub fn is_kernel_task(&self) -> bool {
+		self.task_state.cs == GDT::CODE_SEG as u64
+	}
+
+	pub fn is_privileged(&self) -> bool {
+		self.flags & ProcessFlags::Privileged as u64 != 0
+	}
+
+	pub fn info(&self) -> ProcessInfo {
+		ProcessInfo {
+			pid: self.pid,
+			parent: self.parent,
+			r#type: self.r#type,
+			state: self.state,
+			flags: self.flags,
+			threads: self.live_threads.load(Ordering::SeqCst) as u64,
+			cpu_time: self.cpu_time.load(Ordering::SeqCst),
+			mapped_memory: (&self.mappings).into_iter()
+				.chain(&self.mapping_pages)
+				.map(|mapping| mapping.content.len() as u64)
+				.sum()
+		}
+	}
+
+	/*
+	 * Task state to continue a thread of the process in user mode.
+	 */
+	pub(super) fn user_task_state(&self, registers: [u64; 16], rip: u64, rflags: u64) -> TaskState {
+		TaskState {
+			registers,
+			rip,
+			rflags,
+			gs: cpu::gs::base(),
+			..self.task_state
+		}
+	}
+
+	/*
+	 * The running cores, which the process may use. A new
+	 * process may use all cores, even ones not started yet.
+	 */
+	pub fn affinity(&self) -> u64 {
+		self.affinity & scheduling_cores()
+	}
+
+	pub fn allows_core(&self, core: usize) -> bool {
+		self.affinity & (1 << core) != 0
+	}
+
+	/*
+	 * Sets the priority of the main thread. Must be called before
+	 * spawning, threads change their own priority afterwards.
+	 */
+	pub fn set_priority(&mut self, priority: ProcessPriority) -> &mut Self {
+		self.priority = priority;
+		self
 	}
 
 	pub fn add_mapping(&mut self, virt_addr: u64, content: Box<[u8]>, flags: u64) {
@@ -255,64 +647,329 @@ impl Process {
 		let mapping = self.mappings.push_back(ProcessMapping {
 			virt_addr,
 			content,
-			flags
+			flags,
+			writable: true,
+			copy_on_write: false,
+			shared_memory: None
 		});
-		self.page_table.lock().map(virt_addr, mapping.content.physical_address(), mapping.content.alloc_len(), flags);
+		mapping.map(&self.page_table);
 	}
 
-	pub fn add_unaligned_mapping(&mut self, mut virt_addr: u64, content: &[u8], mut content_offset: usize, mut content_limit: usize, mut flags: u64) {
-		while content_limit > 0 {
-			let mapping_page = if let Some(mapping_page) = (&mut self.mapping_pages).into_iter().find(|page| page.virt_addr == virt_addr & !0xfff) {
+	/*
+	 * Maps the content at the unaligned address. The mapping has the
+	 * memory size, memory after the content, like .bss, is zeroed.
+	 * Pages are shared with unaligned mappings next to it.
+	 */
+	pub fn add_unaligned_mapping(&mut self, virt_addr: u64, content: &[u8], content_offset: usize, content_limit: usize, memory_size: usize, mut flags: u64) -> KernelResult<()> {
+		let end = virt_addr.checked_add(memory_size as u64).ok_or(KernelError::InvalidArgument)?;
+		let mut address = virt_addr;
+		while address < end {
+			let page_address = address & !0xfff;
+			let mapping_page = if let Some(mapping_page) = (&mut self.mapping_pages).into_iter().find(|page| page.virt_addr == page_address) {
 				mapping_page
 			} else {
+				let mut page_content: Box<[u8]> = Box::try_new_sized(0x1000).ok_or(KernelError::OutOfMemory)?;
+				page_content.as_slice_mut().fill(0x0);
 				let page = self.mapping_pages.push_back(ProcessMapping {
-					virt_addr: virt_addr & !0xfff,
-					content: Box::new_sized(0x1000),
-					flags: flags
+					virt_addr: page_address,
+					content: page_content,
+					flags: flags,
+					writable: true,
+					copy_on_write: false,
+					shared_memory: None
 				});
-				self.page_table.lock().map(virt_addr, page.content.physical_address(), 0x1000, flags);
+				self.page_table.lock().map(page_address, page.content.physical_address(), 0x1000, flags);
 				page
 			};
 			if mapping_page.flags != flags {
 				crate::std::log::warn!("Cannot assign unaligned mapping flags. Using existing flags.");
 				flags = mapping_page.flags;
 			}
-			let tocopy = content_limit.min(0x1000 - (content_offset & 0xfff));
-			mapping_page.content.copy_from_slice_with_offset(content_offset, content, content_limit, (virt_addr & 0xfff) as usize);
-			content_limit -= tocopy;
-			content_offset += tocopy;
-			virt_addr += tocopy as u64;
+			let page_end = (page_address + 0x1000).min(end);
+			let start = (address - virt_addr) as usize;
+			let length = (page_end - address) as usize;
+			let copied = content_limit.saturating_sub(start).min(length);
+			let destination = &mut mapping_page.content.as_slice_mut()[(address & 0xfff) as usize..][..length];
+			destination[..copied].copy_from_slice(&content[content_offset + start..][..copied]);
+			destination[copied..].fill(0x0);
+			address = page_end;
+		}
+		Ok(())
+	}
+
+	/*
+	 * Maps zeroed memory with the protection (PROT_*) at the address
+	 * or at a free address, if it´s None. Returns the address.
+	 */
+	pub fn map_anonymous(&mut self, address: Option<u64>, size: u64, protection: u64) -> KernelResult<u64> {
+		let (flags, writable) = protection_flags(protection)?;
+		if size == 0 {
+			return Err(KernelError::InvalidArgument);
+		}
+		let size = size.checked_next_multiple_of(0x1000).ok_or(KernelError::InvalidArgument)? as usize;
+		// Allocated first, sizes beyond the available memory fail before searching an address.
+		let mut content: Box<[u8]> = Box::try_new_sized(size).ok_or(KernelError::OutOfMemory)?;
+		let address = match self.free_address(address, size) {
+			Ok(address) => address,
+			Err(error) => {
+				content.free();
+				return Err(error);
+			}
+		};
+		content.as_slice_mut().fill(0);
+		self.mappings.push_back(ProcessMapping {
+			virt_addr: address,
+			content,
+			flags,
+			writable,
+			copy_on_write: false,
+			shared_memory: None
+		}).map(&self.page_table);
+		Ok(address)
+	}
+
+	/*
+	 * Maps the shared memory at the address or at a free address,
+	 * if it´s None. The mapping holds a reference. Returns the address.
+	 */
+	pub fn map_shared_memory(&mut self, memory: &'static Mutex<SharedMemory>, address: Option<u64>, writable: bool) -> KernelResult<u64> {
+		let size = memory.lock().size();
+		let address = self.free_address(address, size)?;
+
+		memory.acquire();
+		self.mappings.push_back(ProcessMapping {
+			virt_addr: address,
+			content: memory.lock().content(),
+			flags: USER_FLAG | NO_EXECUTE_FLAG,
+			writable,
+			copy_on_write: false,
+			shared_memory: Some(memory)
+		}).map(&self.page_table);
+		Ok(address)
+	}
+
+	/*
+	 * Unmaps the whole mapping at the address with the size. Parts of
+	 * mappings and shared memory can´t be unmapped.
+	 */
+	pub fn unmap(&mut self, address: u64, size: u64) -> KernelResult<()> {
+		let idx = self.find_mapping(address, size)?;
+		let mut mapping = self.mappings.remove(idx);
+		mapping.unmap(&self.page_table);
+		// Other threads of the process mustn´t use the content, before it´s freed.
+		crate::mm::shootdown_tlb(&self.page_table);
+		mapping.free();
+		Ok(())
+	}
+
+	/*
+	 * Changes the protection (PROT_*) of the whole mapping
+	 * at the address with the size.
+	 */
+	pub fn protect(&mut self, address: u64, size: u64, protection: u64) -> KernelResult<()> {
+		let (flags, writable) = protection_flags(protection)?;
+		let idx = self.find_mapping(address, size)?;
+		let mapping = &mut self.mappings[idx];
+		mapping.flags = flags;
+		mapping.writable = writable;
+		mapping.map(&self.page_table);
+		// Other threads of the process mustn´t keep permissions, which were taken away.
+		crate::mm::shootdown_tlb(&self.page_table);
+		Ok(())
+	}
+
+	fn find_mapping(&self, address: u64, size: u64) -> KernelResult<usize> {
+		let size = size.checked_next_multiple_of(0x1000).ok_or(KernelError::InvalidArgument)?;
+		(&self.mappings).into_iter()
+			.position(|mapping| mapping.virt_addr == address && mapping.content.alloc_len() as u64 == size && mapping.shared_memory.is_none())
+			.ok_or(KernelError::InvalidArgument)
+	}
+
+	/*
+	 * Unmaps the shared memory mapped at the address.
+	 */
+	pub fn unmap_shared_memory(&mut self, address: u64) -> KernelResult<()> {
+		let idx = (&self.mappings).into_iter()
+			.position(|mapping| mapping.virt_addr == address && mapping.shared_memory.is_some())
+			.ok_or(KernelError::InvalidArgument)?;
+		let mut mapping = self.mappings.remove(idx);
+		mapping.unmap(&self.page_table);
+		// Other threads of the process mustn´t use the memory, before the reference is released.
+		crate::mm::shootdown_tlb(&self.page_table);
+		mapping.free();
+		Ok(())
+	}
+
+	/*
+	 * Checks, that the page aligned address is free for a mapping with
+	 * the size. Without an address, the lowest free one of MAPPING_ADDRESS
+	 * and the ends of the mappings above it is picked.
+	 */
+	fn free_address(&self, address: Option<u64>, size: usize) -> KernelResult<u64> {
+		match address {
+			Some(address) if address & 0xfff == 0 && self.is_free(address, size) => Ok(address),
+			Some(_) => Err(KernelError::InvalidArgument),
+			None => (&self.mappings).into_iter()
+				.chain(&self.mapping_pages)
+				.map(|mapping| mapping.virt_addr + mapping.content.alloc_len() as u64)
+				.filter(|end| *end >= MAPPING_ADDRESS)
+				.chain([MAPPING_ADDRESS])
+				.filter(|address| self.is_free(*address, size))
+				.min()
+				.ok_or(KernelError::OutOfMemory)
+		}
+	}
+
+	/*
+	 * Whether the range is in user space and neither a mapping nor
+	 * anything else is mapped there.
+	 */
+	fn is_free(&self, address: u64, size: usize) -> bool {
+		if address == 0x0 || address.checked_add(size as u64).is_none_or(|end| end > USER_ADDRESS_LIMIT) {
+			return false;
+		}
+		if (&self.mappings).into_iter().chain(&self.mapping_pages).any(|mapping| mapping.overlaps(address, size)) {
+			return false;
+		}
+		self.page_table.lock().is_unmapped(address, size)
+	}
+
+	/*
+	 * Handles a write fault at the address. Returns false, if the address
+	 * isn´t in a writable copy on write mapping of the process.
+	 */
+	pub fn resolve_copy_on_write(&mut self, address: u64) -> bool {
+		let page_table = &self.page_table;
+		let Some(mapping) = (&mut self.mappings).into_iter()
+			.chain(&mut self.mapping_pages)
+			.find(|mapping| mapping.contains(address)) else {
+			return false;
+		};
+		if !mapping.writable {
+			false
+		} else if mapping.copy_on_write {
+			mapping.resolve_copy_on_write(page_table);
+			true
+		} else {
+			// Another thread of the process resolved the fault first.
+			page_table.lock().is_writable(address)
 		}
 	}
 }
 
 impl Mutex<Process> {
-	pub fn mutex_switch(&'static self) {
-		if let Some(current_process) = current_process() {
-			let mut current_process_lock = current_process.lock();
-			if current_process_lock.state == ProcessState::RUNNING {
-				current_process_lock.state = ProcessState::IDLE;
+	/*
+	 * Terminates every thread of the process. It´s called by
+	 * one of its threads, so it doesn´t return. Files and ports are
+	 * closed right away, so readers of its pipes and senders to its
+	 * ports don´t wait for the reap.
+	 */
+	pub fn kill(&'static self, exit_code: u64) -> ! {
+		self.terminate(exit_code);
+		let pid = {
+			let mut process = self.lock();
+			process.files.close_all();
+			process.pid
+		};
+		Port::destroy_all(pid);
+		exit_current_thread(exit_code)
+	}
+
+	/*
+	 * Terminates every thread of the process except the current one.
+	 * Running threads stop, when they´re switched out the next time.
+	 */
+	pub fn terminate(&'static self, exit_code: u64) {
+		// The timer mustn´t switch, while a lock is held.
+		let interrupts = interrupts_enabled();
+		cli();
+		{
+			let mut process = self.lock();
+			if process.state == ProcessState::RUNNING {
+				process.state = ProcessState::KILLED;
+				process.exit_code = exit_code;
 			}
 		}
-		{
-			let mut locked = self.lock();
-			locked.state = ProcessState::RUNNING;
-
-			PID_PER_CPU.set(locked.pid);
+		let current = current_thread();
+		for thread in threads() {
+			if core::ptr::eq(thread.process, self) && !current.is_some_and(|current| core::ptr::eq(current, thread)) {
+				thread.lock().terminate(exit_code);
+			}
 		}
+		if interrupts {
+			sti();
+		}
+	}
 
-		self.page_table.load();
+	/*
+	 * Starts a thread at entry_addr in the process. The argument
+	 * is passed in rdi. Returns the tid or None, when the process exits.
+	 */
+	pub fn create_thread(&'static self, entry_addr: u64, stack: u64, argument: u64) -> Option<u64> {
+		let (task_state, priority) = {
+			let process = self.lock();
+			if process.state != ProcessState::RUNNING {
+				return None;
+			}
+			let mut task_state = TaskState::new(process.task_state.cs as u16, stack, entry_addr)?;
+			task_state.rflags = process.task_state.rflags;
+			task_state.registers[5] = argument;
+			process.live_threads.fetch_add(1, Ordering::SeqCst);
+			(task_state, process.priority)
+		};
+		Some(Thread::new(self, task_state, Box::null(), priority).spawn())
+	}
 
-		crate::mm::set_current_page_table(&self.page_table);
-		cpu::lapic::LAPIC::end_of_interrupt();
+	/*
+	 * Creates a child with the address space of the process. The
+	 * contents are shared copy on write. The main thread of the child
+	 * continues at rip with the registers. Returns the pid of the child
+	 * or None, when the process exits.
+	 */
+	pub fn fork(&'static self, registers: [u64; 16], rip: u64, rflags: u64) -> Option<u64> {
+		let child = {
+			let mut process_lock = self.lock();
+			let process = &mut *process_lock;
+			if process.state != ProcessState::RUNNING {
+				return None;
+			}
+			let mut child = Process::new(ProcessPrivilage::USER, rip)?;
+			child.task_state.registers = registers;
+			child.task_state.rflags = rflags;
+			child.task_state.cs = process.task_state.cs;
+			child.task_state.ds = process.task_state.ds;
+			child.priority = process.priority;
+			child.affinity = process.affinity;
+			child.signals = process.signals.inherit();
+			child.files = process.files.share();
+			child.working_directory = process.working_directory.clone();
 
-		self.task_state.load()
+			for mapping in &mut process.mappings {
+				child.mappings.push_back(mapping.share(&process.page_table, &child.page_table));
+			}
+			for mapping in &mut process.mapping_pages {
+				child.mapping_pages.push_back(mapping.share(&process.page_table, &child.page_table));
+			}
+			child
+		};
+		let pid = child.pid;
+		child.spawn();
+		Some(pid)
 	}
-	pub fn kill(&self) -> ! {
-		self.lock().state = ProcessState::KILLED;
-		r#yield();
-		unreachable!()
+
+	/*
+	 * Restricts the threads of the process to the cores in the mask.
+	 * Fails, if none of the cores runs the scheduler.
+	 */
+	pub fn set_affinity(&self, affinity: u64) -> KernelResult<()> {
+		if affinity & scheduling_cores() == 0 {
+			return Err(KernelError::InvalidArgument);
+		}
+		self.lock().affinity = affinity;
+		AFFINITY_GENERATION.fetch_add(1, Ordering::SeqCst);
+		Ok(())
 	}
+
 	pub fn assign_flags(&self, flags: ProcessFlags) {
 		self.lock().flags |= flags as u64;
 	}
@@ -320,47 +977,510 @@ impl Mutex<Process> {
 
 unsafe impl Sync for Process {}
 
+/*
+ * Page entry flags and whether the mapping is writable for the
+ * protection. Mappings are always readable.
+ */
+fn protection_flags(protection: u64) -> KernelResult<(u64, bool)> {
+	if protection & !(PROT_READ | PROT_WRITE | PROT_EXEC) != 0 {
+		return Err(KernelError::InvalidArgument);
+	}
+	let mut flags = USER_FLAG;
+	if protection & PROT_EXEC == 0 {
+		flags |= NO_EXECUTE_FLAG;
+	}
+	Ok((flags, protection & PROT_WRITE != 0))
+}
+
+/*
+ * Connects the LAPIC timer of the current core with the scheduler.
+ * Every time slice the timer is rearmed, expired sleepers are woken
+ * and the current thread yields.
+ * Threads are queued on the core afterwards.
+ */
 pub fn init_yield_timer() {
-	//cpu::connect_signal(cpu::TIMER, |_| r#yield());
+	SCHEDULING_CORES.set(true);
+	cpu::connect_signal(cpu::TIMER, |_| {
+		LAPIC::arm_timer(time_slice());
+		super::sleep::wake_sleepers();
+		r#yield();
+	});
 }
 
+pub fn time_slice() -> u64 {
+	*TIME_SLICE.lock()
+}
+
+/*
+ * Sets the time slice in milliseconds. Cores apply it
+ * with the next timer interrupt.
+ */
+pub fn set_time_slice(ms: u64) {
+	*TIME_SLICE.lock() = ms.max(1);
+}
+
+/*
+ * Switches to the next idle thread. The current thread continues,
+ * when no other thread is idle and it´s still running.
+ * Yielding is skipped, when the thread list or the run queues of
+ * the core are locked, because the yield may come from the timer
+ * interrupt of the core, which holds the lock.
+ */
 pub fn r#yield() {
-	if PROCESSES.is_locked() {
+	if Thread::is_locked() || RUN_QUEUES.deref().is_locked() {
 		return;
 	}
+	let interrupts = interrupts_enabled();
 	cli();
-	let idx = {
-		let mut next_process = NEXT_PROCESS.lock();
-		let org_next_process = *next_process;
-		let processes = PROCESSES.lock();
-		let mut processlock = &processes[*next_process];
-
-		while !((processlock.state == ProcessState::IDLE) && processlock.task_state != *STATE_PER_CPU.deref_mut()) {
-			*next_process = (*next_process + 1) % processes.len();
-			if *next_process == org_next_process {
-				// No idle process found.
-				sti();
-				hltloop();
+	account_cpu_time();
+
+	if let Some(next) = next_thread() {
+		switch_to(Some(next));
+	} else if current_thread().is_some_and(|thread| thread.state != ThreadState::RUNNING) {
+		switch_to(None);
+	}
+
+	if interrupts {
+		sti();
+	}
+}
+
+/*
+ * Adds the time since the last accounting to the process of the
+ * current thread. Time without a thread is idle time.
+ */
+fn account_cpu_time() {
+	let now = clock::monotonic_ns();
+	if let Some(current) = current_thread() {
+		current.process.cpu_time.fetch_add(now.saturating_sub(*LAST_ACCOUNTING.deref()), Ordering::SeqCst);
+	}
+	LAST_ACCOUNTING.set(now);
+}
+
+/*
+ * Picks the next thread for the current core. Threads of other
+ * cores are taken, when the core has none left or periodically,
+ * when another core has more threads queued.
+ */
+fn next_thread() -> Option<&'static Mutex<Thread>> {
+	let core = current_core() as usize;
+	queue_woken_threads();
+	migrate_threads(core);
+
+	let counter = SCHEDULE_COUNTER.deref_mut();
+	*counter += 1;
+	if counter.is_multiple_of(BALANCE_INTERVAL) && let Some(next) = steal_thread(core, true) {
+		return Some(next);
+	}
+	let next = take_thread(&mut RUN_QUEUES.deref().lock(), core);
+	next.or_else(|| steal_thread(core, false))
+}
+
+/*
+ * Takes the next idle thread, which may run on the core, from the run
+ * queues and marks it as running. Queued threads, which were killed in
+ * the meantime, are dropped. Locked threads are queued again, the lock
+ * may be held by the interrupted code on this core.
+ */
+fn take_thread(run_queues: &mut RunQueues, core: usize) -> Option<&'static Mutex<Thread>> {
+	for _ in 0..run_queues.len() {
+		let next = run_queues.pop()?;
+		if !next.process.allows_core(core) {
+			run_queues.push(next, next.priority());
+			continue;
+		}
+		if let Some(mut thread) = next.try_lock() {
+			if thread.state == ThreadState::IDLE {
+				thread.state = ThreadState::RUNNING;
+				thread.on_cpu = true;
+				return Some(next);
 			}
-			processlock = &processes[*next_process];
+		} else {
+			run_queues.push(next, next.priority());
+		}
+	}
+	None
+}
+
+/*
+ * Takes a thread from the run queues of another core. Balancing only
+ * takes from the busiest core, if it has more threads queued than the
+ * current one. Locked run queues are skipped, so cores never wait for
+ * each other.
+ */
+fn steal_thread(core: usize, balance: bool) -> Option<&'static Mutex<Thread>> {
+	let mut victims = (0..MAX_CORES).filter(|victim| *victim != core && *SCHEDULING_CORES.get(*victim));
+	if balance {
+		let victim = victims.max_by_key(|victim| RUN_QUEUES.get(*victim).len())?;
+		if RUN_QUEUES.get(victim).len() <= RUN_QUEUES.get(core).len() + 1 {
+			return None;
+		}
+		take_thread(&mut *RUN_QUEUES.get(victim).try_lock()?, core)
+	} else {
+		victims.find_map(|victim| take_thread(&mut *RUN_QUEUES.get(victim).try_lock()?, core))
+	}
+}
+
+/*
+ * Moves queued threads, which mustn´t run on the core
+ * anymore, to other cores after an affinity changed.
+ */
+fn migrate_threads(core: usize) {
+	let generation = AFFINITY_GENERATION.load(Ordering::SeqCst);
+	if *SEEN_AFFINITY_GENERATION.deref() == generation {
+		return;
+	}
+	SEEN_AFFINITY_GENERATION.set(generation);
+	loop {
+		// The run queues of the core are unlocked, before another core´s are locked.
+		let Some(thread) = RUN_QUEUES.get(core).lock().remove_first(|thread| !thread.process.allows_core(core)) else {
+			break;
+		};
+		push_to_core(least_loaded_core(thread), thread, thread.priority());
+	}
+}
+
+/*
+ * Queues the thread on the least loaded core, it may run on.
+ */
+pub(super) fn enqueue(thread: &'static Mutex<Thread>, priority: ProcessPriority) {
+	push_to_core(least_loaded_core(thread), thread, priority);
+}
+
+fn push_to_core(core: usize, thread: &'static Mutex<Thread>, priority: ProcessPriority) {
+	let interrupts = interrupts_enabled();
+	cli();
+	RUN_QUEUES.get(core).lock().push(thread, priority);
+	if interrupts {
+		sti();
+	}
+}
+
+fn least_loaded_core(thread: &'static Mutex<Thread>) -> usize {
+	(0..MAX_CORES)
+		.filter(|core| *SCHEDULING_CORES.get(*core) && thread.process.allows_core(*core))
+		.min_by_key(|core| RUN_QUEUES.get(*core).len())
+		.unwrap_or(current_core() as usize)
+}
+
+// Mask of the cores, which run the scheduler.
+fn scheduling_cores() -> u64 {
+	SCHEDULING_CORES.iter()
+		.enumerate()
+		.filter(|(_, scheduling)| **scheduling)
+		.fold(0, |mask, (core, _)| mask | 1 << core)
+}
+
+/*
+ * Queues all threads, which were woken since the last call.
+ */
+fn queue_woken_threads() {
+	if !PENDING_WAKEUPS.swap(false, Ordering::SeqCst) {
+		return;
+	}
+	for thread in threads() {
+		if !thread.wakeup.load(Ordering::SeqCst) {
+			continue;
+		}
+		let Some(mut thread_lock) = thread.try_lock() else {
+			PENDING_WAKEUPS.store(true, Ordering::SeqCst);
+			continue;
+		};
+		let queue = thread_lock.wake();
+		let priority = thread_lock.priority();
+		// The thread lock is released first, take_thread locks in the opposite order.
+		drop(thread_lock);
+		if queue {
+			enqueue(thread, priority);
+		}
+	}
+}
+
+/*
+ * Blocks the current thread on the wait channel, until it´s woken
+ * by wake_channel. The condition is checked after blocking,
+ * so a wake up between checking and blocking isn´t lost.
+ * Returns false, if the current thread can´t be blocked.
+ */
+pub fn block_current(channel: u64, condition: &dyn Fn() -> bool) -> bool {
+	let Some(current) = current_thread() else {
+		return false;
+	};
+	let interrupts = interrupts_enabled();
+	cli();
+
+	// The lock may be held by the code, which waits.
+	let Some(mut thread) = current.try_lock() else {
+		if interrupts {
+			sti();
+		}
+		return false;
+	};
+	thread.wait_channel.store(channel, Ordering::SeqCst);
+	thread.state = ThreadState::BLOCKED;
+	drop(thread);
+	core::sync::atomic::fence(Ordering::SeqCst);
+
+	if !condition() {
+		r#yield();
+	}
+
+	// Yielding is skipped, while the scheduler is locked.
+	let mut thread = current.lock();
+	if matches!(thread.state, ThreadState::BLOCKED | ThreadState::IDLE) {
+		thread.state = ThreadState::RUNNING;
+	}
+	thread.wait_channel.store(0, Ordering::SeqCst);
+	drop(thread);
+
+	if interrupts {
+		sti();
+	}
+	true
+}
+
+/*
+ * Wakes all threads blocked on the wait channel. No lock is taken,
+ * so it´s safe to call while unlocking any lock.
+ * The threads are queued with the next scheduling decision.
+ */
+pub fn wake_channel(channel: u64) {
+	for thread in threads() {
+		if thread.wait_channel.load(Ordering::SeqCst) == channel {
+			thread.wakeup.store(true, Ordering::SeqCst);
+			PENDING_WAKEUPS.store(true, Ordering::SeqCst);
 		}
-		*next_process
+	}
+}
+
+/*
+ * Saves the current context and continues with the next thread.
+ * None switches to the init process of the core.
+ * Interrupts must be disabled. No thread lock is taken, because
+ * the interrupted code may hold the lock of the current thread.
+ * The running state makes sure, that no other core touches both.
+ */
+fn switch_to(next: Option<&'static Mutex<Thread>>) {
+	let to_save = if let Some(current) = current_thread() {
+		core::ptr::addr_of!(current.stack_pointer) as *mut u64
+	} else {
+		IDLE_STACK_POINTER.deref_mut() as *mut u64
+	};
+	PREVIOUS_TID_PER_CPU.set(*TID_PER_CPU.deref());
+
+	let to_load = if let Some(next) = next {
+		TID_PER_CPU.set(next.tid);
+
+		// Set before loading, so TLB shootdowns for the table reach the core.
+		crate::mm::set_current_page_table(&next.process.page_table);
+		next.process.page_table.load();
+		cpu::gdt::set_kernel_stack(next.kernel_stack_top());
+		cpu::gs::set_syscall_stack(next.kernel_stack_top());
+		next.stack_pointer
+	} else {
+		TID_PER_CPU.set(u64::MAX);
+
+		let page_table = crate::mm::initial_page_table();
+		crate::mm::set_current_page_table(page_table);
+		page_table.lock().load();
+		*IDLE_STACK_POINTER.deref()
 	};
 
-	let process = &PROCESSES.read()[idx];
-	Process::from_pid(process.pid).as_ref().unwrap().mutex_switch();
+	unsafe {
+		asm!("call rax",
+			 in("rax") switch_context as *const () as u64 + crate::mm::kernel_offset(),
+			 in("rdi") to_save,
+			 in("rsi") to_load,
+			 clobber_abi("sysv64"));
+	}
+
+	finish_switch();
+}
+
+/*
+ * Called after every switch on the new stack.
+ * Marks the previous thread as idle and queues it again,
+ * if it´s still running.
+ * Spins instead of yielding, because yielding would switch
+ * again before the previous thread is released.
+ */
+fn finish_switch() {
+	if let Some(previous) = Thread::from_tid(*PREVIOUS_TID_PER_CPU.deref()) {
+		let requeue = loop {
+			if let Some(mut previous_lock) = previous.try_lock() {
+				previous_lock.on_cpu = false;
+				// Idle, when it was woken while being switched out.
+				if matches!(previous_lock.state, ThreadState::RUNNING | ThreadState::IDLE) {
+					previous_lock.state = ThreadState::IDLE;
+					break Some(previous_lock.priority());
+				}
+				if previous_lock.state == ThreadState::KILLED {
+					previous_lock.set_zombie();
+				}
+				break None;
+			}
+			core::hint::spin_loop();
+		};
+		// The thread lock is released first, take_thread locks in the opposite order.
+		// The thread stays on the core, unless it mustn´t run on it anymore.
+		if let Some(priority) = requeue {
+			let core = current_core() as usize;
+			if previous.process.allows_core(core) {
+				push_to_core(core, previous, priority);
+			} else {
+				enqueue(previous, priority);
+			}
+		}
+	}
+	PREVIOUS_TID_PER_CPU.set(u64::MAX);
+}
+
+/*
+ * First method of every thread. Jumps into the
+ * initial task state after finishing the switch.
+ */
+pub(super) fn thread_entry() -> ! {
+	finish_switch();
+
+	let mut task_state = current_thread()
+		.expect("Attempt to enter thread without tid.")
+		.task_state;
+	task_state.gs = cpu::gs::base();
+	task_state.jump()
+}
+
+/*
+ * Lists every process, which wasn´t reaped. Processes aren´t locked,
+ * so processes holding their lock, e.g. hung ones, are listed too.
+ */
+pub fn process_table() -> Vec<ProcessInfo> {
+	let mut table = Vec::new();
+	for process in PROCESSES.read().into_iter() {
+		let process = process.read();
+		if process.state != ProcessState::REAPED {
+			table.push_back(process.info());
+		}
+	}
+	table
+}
+
+pub fn current_thread() -> Option<&'static Mutex<Thread>> {
+	Thread::from_tid(*TID_PER_CPU.deref())
 }
 
 pub fn current_process() -> Option<&'static Mutex<Process>> {
-	Process::from_pid(*PID_PER_CPU.deref_mut())
+	current_thread().map(|thread| thread.process)
+}
+
+/*
+ * The exit code of the last thread becomes the exit code of the process,
+ * unless the process was killed before.
+ */
+pub fn exit_current_thread(exit_code: u64) -> ! {
+	// The timer mustn´t switch, while the thread lock is held.
+	cli();
+	let thread = current_thread().expect("Attempt to kill boot setup task.");
+	let last_pid = {
+		// Threads exit one after another, so exactly one of them sees, that it´s the last.
+		let mut process = thread.process.lock();
+		thread.lock().terminate(exit_code);
+		let last = !threads().into_iter()
+			.any(|other| core::ptr::eq(other.process, thread.process) && other.is_alive());
+		if last && process.state == ProcessState::RUNNING {
+			process.state = ProcessState::KILLED;
+			process.exit_code = exit_code;
+			process.files.close_all();
+			Some(process.pid)
+		} else {
+			None
+		}
+	};
+	if let Some(pid) = last_pid {
+		Port::destroy_all(pid);
+	}
+	loop {
+		r#yield();
+	}
 }
 
-pub fn current_task_state() -> &'static TaskState {
-	STATE_PER_CPU.deref()
+/*
+ * Forks the current process from a syscall. The child
+ * returns from the syscall with zero.
+ */
+pub fn fork_current_process() -> Option<u64> {
+	let thread = current_thread()?;
+	let frame = SyscallFrame::from_stack(thread.kernel_stack_top());
+	thread.process.fork(frame.user_registers(0x0), frame.rcx, frame.r11)
 }
 
-pub fn exit_current_process() -> ! {
+pub fn exit_current_process(exit_code: u64) -> ! {
 	current_process()
 		.expect("Attempt to kill boot setup task.")
-		.kill()
+		.kill(exit_code)
+}
+
+/*
+ * Blocks until the thread of the current process exited and reaps it.
+ * Returns the exit code of the thread or None, if there´s no such thread.
+ */
+pub fn join_thread(tid: u64) -> Option<u64> {
+	let process = current_process()?;
+	if current_thread()?.tid == tid {
+		return None;
+	}
+	loop {
+		let thread = Thread::from_tid(tid).filter(|thread| core::ptr::eq(thread.process, process))?;
+		{
+			let mut thread_lock = thread.lock();
+			if thread_lock.state == ThreadState::ZOMBIE {
+				return Some(thread_lock.reap());
+			}
+		}
+		EXITED_THREADS.wait_until(|| thread.tid != tid || matches!(thread.state, ThreadState::ZOMBIE | ThreadState::REAPED));
+	}
+}
+
+/*
+ * Blocks until a child of the current process exited and reaps it.
+ * A pid of u64::MAX waits for any child. Returns the pid and the
+ * exit code of the child or None, if there´s no such child.
+ */
+pub fn wait_for_child(pid: u64) -> Option<(u64, u64)> {
+	let parent = current_process()?.pid;
+	loop {
+		let mut has_child = false;
+		for child in PROCESSES.read() {
+			let mut child = child.lock();
+			if child.parent != parent || child.state == ProcessState::REAPED || (pid != u64::MAX && child.pid != pid) {
+				continue;
+			}
+			if child.is_zombie() {
+				child.reap();
+				return Some((child.pid, child.exit_code));
+			}
+			has_child = true;
+		}
+		if !has_child {
+			return None;
+		}
+		EXITED_THREADS.wait_until(|| PROCESSES.read().into_iter().any(|child| {
+			child.parent == parent && child.is_zombie() && (pid == u64::MAX || child.pid == pid)
+		}));
+	}
+}
+
+/*
+ * Reaps zombies, whose parent exited or which never had one.
+ * Nobody would wait for them otherwise.
+ */
+fn reap_orphans() {
+	for process in PROCESSES.read() {
+		if !process.is_zombie() || Process::from_pid(process.parent).is_some_and(|parent| parent.is_alive()) {
+			continue;
+		}
+		let mut process = process.lock();
+		if process.is_zombie() {
+			process.reap();
+		}
+	}
 }
@@ -0,0 +1,119 @@
+/*
+ * Shared memory is physical memory, which several processes map. Open
+ * files and mappings both hold a reference, so the memory outlives the
+ * descriptor it was created with. Named shared memory is found by other
+ * processes, as long as it exists.
+ */
+
+use crate::std::{
+	Box,
+	String,
+	Vec,
+	VecBase,
+	Mutex,
+	KernelError,
+	KernelResult
+};
+use abi::memory::{
+	PAGE_SIZE,
+	MAX_SHARED_MEMORY_SIZE
+};
+use core::ops::Index;
+
+pub struct SharedMemory {
+	name: Option<String>,
+	content: Box<[u8]>,
+	// Open files and mappings referring to the memory. Slots without are reused.
+	references: usize
+}
+
+/*
+ * Shared memory is never moved, so open files and mappings refer to it directly.
+ */
+static SHARED_MEMORY: Mutex<Vec<Mutex<SharedMemory>>> = Mutex::new(Vec::new());
+
+impl SharedMemory {
+	/*
+	 * Allocates zeroed shared memory with one reference.
+	 * Names have to be unique among existing shared memory.
+	 */
+	pub fn create(size: u64, name: Option<String>) -> KernelResult<&'static Mutex<SharedMemory>> {
+		if size == 0 || size > MAX_SHARED_MEMORY_SIZE {
+			return Err(KernelError::InvalidArgument);
+		}
+		let size = size.next_multiple_of(PAGE_SIZE) as usize;
+
+		let mut shared_memory = SHARED_MEMORY.lock();
+		if let Some(name) = &name && SharedMemory::find(name).is_some() {
+			return Err(KernelError::AlreadyExists);
+		}
+		let mut content: Box<[u8]> = Box::try_new_sized(size).ok_or(KernelError::OutOfMemory)?;
+		content.as_slice_mut().fill(0);
+		let memory = SharedMemory {
+			name,
+			content,
+			references: 1
+		};
+
+		// Slots are only reused with the table locked, so nobody else takes the free slot.
+		if let Some(slot) = SHARED_MEMORY.read().into_iter().find(|slot| slot.read().references == 0) {
+			*slot.lock() = memory;
+			return Ok(slot);
+		}
+		shared_memory.push_back(Mutex::new(memory));
+		Ok(SHARED_MEMORY.read().index(shared_memory.len() - 1))
+	}
+
+	/*
+	 * Returns the shared memory with the name and adds a reference to it.
+	 */
+	pub fn open(name: &str) -> KernelResult<&'static Mutex<SharedMemory>> {
+		let _shared_memory = SHARED_MEMORY.lock();
+		let memory = SharedMemory::find(name).ok_or(KernelError::NotFound)?;
+		memory.acquire();
+		Ok(memory)
+	}
+
+	/*
+	 * Requires the table to be locked.
+	 */
+	fn find(name: &str) -> Option<&'static Mutex<SharedMemory>> {
+		SHARED_MEMORY.read().into_iter().find(|slot| {
+			let slot = slot.read();
+			slot.references > 0 && slot.name.as_ref().is_some_and(|slot_name| slot_name.as_str() == name)
+		})
+	}
+
+	pub fn size(&self) -> usize {
+		self.content.alloc_len()
+	}
+
+	/*
+	 * Another box of the memory for a mapping. It mustn´t be freed.
+	 */
+	pub fn content(&self) -> Box<[u8]> {
+		unsafe {
+			self.content.alias()
+		}
+	}
+}
+
+unsafe impl Sync for SharedMemory {}
+
+impl Mutex<SharedMemory> {
+	pub fn acquire(&self) {
+		self.lock().references += 1;
+	}
+
+	/*
+	 * Drops a reference. The memory is freed with the last one.
+	 */
+	pub fn release(&self) {
+		let mut memory = self.lock();
+		memory.references -= 1;
+		if memory.references == 0 {
+			memory.name = None;
+			core::mem::replace(&mut memory.content, Box::null()).free();
+		}
+	}
+}
@@ -0,0 +1,297 @@
+/*
+ * POSIX like signals. Signals are sent to processes and handled by the
+ * first of their threads, which returns to user mode from a syscall or
+ * a fault. Threads blocked in a syscall handle them after it returned.
+ * Signals without handler apply their default action, when they´re sent.
+ */
+
+use super::scheduler::{
+	Process,
+	current_thread,
+	exit_current_process
+};
+use super::thread::Thread;
+use super::usercopy::{
+	USER_ADDRESS_LIMIT,
+	read_from_user,
+	write_to_user
+};
+use crate::hw::cpu::{
+	ExceptionFrame,
+	syscall::SyscallFrame
+};
+use crate::std::{
+	Mutex,
+	KernelError,
+	KernelResult
+};
+use core::sync::atomic::{
+	AtomicU64,
+	Ordering
+};
+
+pub use abi::signal::{
+	SIGILL,
+	SIGTRAP,
+	SIGBUS,
+	SIGFPE,
+	SIGKILL,
+	SIGSEGV,
+	SIGPIPE,
+	SIGCHLD,
+	SIGNAL_AMOUNT,
+	SIGNAL_EXIT_CODE,
+	SIG_DFL,
+	SIG_IGN
+};
+
+// Below the stack pointer, user code may use the stack without moving it.
+const RED_ZONE: u64 = 0x80;
+// Flags, which the user may restore with sigreturn.
+const USER_RFLAGS: u64 = 0xcd5;
+
+#[derive(Clone, Copy)]
+pub struct SignalAction {
+	pub handler: u64,
+	// Return address of the handler. It has to call sigreturn.
+	pub restorer: u64
+}
+
+/*
+ * Signal state of a process.
+ */
+pub struct Signals {
+	actions: [SignalAction; SIGNAL_AMOUNT],
+	// Bit n is set, while signal n waits for a handler.
+	pending: AtomicU64
+}
+
+/*
+ * Pushed on the user stack, before the handler is entered. The
+ * stack pointer points to restorer, when the handler starts.
+ */
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct SignalFrame {
+	restorer: u64,
+	signal: u64,
+	blocked_signals: u64,
+	registers: [u64; 16],
+	rip: u64,
+	rflags: u64
+}
+
+impl SignalAction {
+	const DEFAULT: SignalAction = SignalAction {
+		handler: SIG_DFL,
+		restorer: 0x0
+	};
+}
+
+impl Signals {
+	pub const fn new() -> Signals {
+		Signals {
+			actions: [SignalAction::DEFAULT; SIGNAL_AMOUNT],
+			pending: AtomicU64::new(0)
+		}
+	}
+
+	/*
+	 * Handlers are inherited by forked children, pending signals not.
+	 */
+	pub fn inherit(&self) -> Signals {
+		Signals {
+			actions: self.actions,
+			pending: AtomicU64::new(0)
+		}
+	}
+
+	/*
+	 * Replaces the action of the signal and returns the previous one.
+	 * Returns None for unknown signals, SIGKILL and handlers
+	 * outside of user space.
+	 */
+	pub fn set_action(&mut self, signal: u64, action: SignalAction) -> Option<SignalAction> {
+		if signal == 0 || signal == SIGKILL || signal as usize >= SIGNAL_AMOUNT ||
+			action.handler >= USER_ADDRESS_LIMIT || action.restorer >= USER_ADDRESS_LIMIT ||
+			(action.handler > SIG_IGN && action.restorer == 0x0) {
+			return None;
+		}
+		Some(core::mem::replace(&mut self.actions[signal as usize], action))
+	}
+
+	fn has_handler(&self, signal: u64) -> bool {
+		self.actions[signal as usize].handler > SIG_IGN
+	}
+
+	fn is_ignored(&self, signal: u64) -> bool {
+		let handler = self.actions[signal as usize].handler;
+		handler == SIG_IGN || (handler == SIG_DFL && signal == SIGCHLD)
+	}
+}
+
+/*
+ * Sends the signal to the process. Signal zero only checks, whether the
+ * process exists.
+ */
+pub fn send_signal(pid: u64, signal: u64) -> KernelResult<()> {
+	if signal as usize >= SIGNAL_AMOUNT {
+		return Err(KernelError::InvalidArgument);
+	}
+	let process = Process::from_pid(pid).ok_or(KernelError::NoProcess)?;
+	if !may_signal(process) {
+		return Err(KernelError::NotPermitted);
+	}
+	if signal == 0 || (process.signals.is_ignored(signal) && signal != SIGKILL) {
+		return Ok(());
+	}
+	if signal != SIGKILL && process.signals.has_handler(signal) {
+		process.signals.pending.fetch_or(1 << signal, Ordering::SeqCst);
+	} else if current_thread().is_some_and(|thread| core::ptr::eq(thread.process, process)) {
+		process.kill(SIGNAL_EXIT_CODE + signal);
+	} else {
+		process.terminate(SIGNAL_EXIT_CODE + signal);
+	}
+	Ok(())
+}
+
+/*
+ * Processes may signal themselves and their children, privileged ones
+ * every user process. Kernel tasks are never signaled by processes,
+ * but the kernel may signal every process.
+ */
+fn may_signal(process: &'static Mutex<Process>) -> bool {
+	let Some(sender) = current_thread().map(|thread| thread.process) else {
+		return true;
+	};
+	if sender.is_kernel_task() {
+		return true;
+	}
+	!process.is_kernel_task() && (core::ptr::eq(sender, process) || process.parent == sender.pid || sender.is_privileged())
+}
+
+/*
+ * Called before a syscall returns to user mode. A pending signal
+ * redirects the return to its handler. The return value
+ * is restored by sigreturn.
+ */
+pub fn deliver_signals(frame: &mut SyscallFrame, return_value: u64) -> u64 {
+	let Some(thread) = current_thread() else {
+		return return_value;
+	};
+	let Some(signal) = next_pending_signal(thread) else {
+		return return_value;
+	};
+	let handler = thread.process.signals.actions[signal as usize].handler;
+	if let Some(stack) = push_signal_frame(thread, signal, frame.user_registers(return_value), frame.rcx, frame.r11) {
+		frame.rsp = stack;
+		frame.rcx = handler;
+		frame.rdi = signal;
+	} else {
+		exit_current_process(SIGNAL_EXIT_CODE + SIGSEGV);
+	}
+	return_value
+}
+
+/*
+ * Enters the handler of the signal, which was caused by a fault
+ * in user mode. Returns false, if the process doesn´t handle it.
+ */
+pub fn deliver_fault(frame: &mut ExceptionFrame, signal: u64) -> bool {
+	let Some(thread) = current_thread() else {
+		return false;
+	};
+	if !thread.process.signals.has_handler(signal) || thread.blocked_signals & 1 << signal != 0 {
+		return false;
+	}
+	let registers = [
+		frame.rax,
+		frame.rbx,
+		frame.rcx,
+		frame.rdx,
+		frame.rsi,
+		frame.rdi,
+		frame.rbp,
+		frame.rsp,
+		frame.r8,
+		frame.r9,
+		frame.r10,
+		frame.r11,
+		frame.r12,
+		frame.r13,
+		frame.r14,
+		frame.r15
+	];
+	let Some(stack) = push_signal_frame(thread, signal, registers, frame.rip, frame.rflags) else {
+		return false;
+	};
+	frame.rsp = stack;
+	frame.rip = thread.process.signals.actions[signal as usize].handler;
+	frame.rdi = signal;
+	true
+}
+
+/*
+ * Continues the state, which was interrupted by a signal. It´s called
+ * by the restorer, so the signal frame is right below the stack pointer.
+ */
+pub fn return_from_signal() -> ! {
+	let thread = current_thread().expect("Attempt to return from signal in kernel task.");
+	let address = SyscallFrame::from_stack(thread.kernel_stack_top()).rsp.wrapping_sub(0x8);
+	let Ok(signal_frame) = read_from_user::<SignalFrame>(address) else {
+		exit_current_process(SIGNAL_EXIT_CODE + SIGSEGV);
+	};
+	if signal_frame.rip >= USER_ADDRESS_LIMIT {
+		exit_current_process(SIGNAL_EXIT_CODE + SIGSEGV);
+	}
+	thread.lock().blocked_signals = signal_frame.blocked_signals;
+	thread.process
+		.user_task_state(signal_frame.registers, signal_frame.rip, signal_frame.rflags & USER_RFLAGS | 0x202)
+		.jump()
+}
+
+/*
+ * Takes a pending signal of the process, which the thread doesn´t block.
+ * Signals, whose handler was removed meanwhile, apply their default action.
+ */
+fn next_pending_signal(thread: &'static Mutex<Thread>) -> Option<u64> {
+	let signals = &thread.process.signals;
+	loop {
+		let deliverable = signals.pending.load(Ordering::SeqCst) & !thread.blocked_signals;
+		if deliverable == 0 {
+			return None;
+		}
+		let signal = deliverable.trailing_zeros() as u64;
+		// Another thread may have taken the signal.
+		if signals.pending.fetch_and(!(1 << signal), Ordering::SeqCst) & 1 << signal == 0 || signals.is_ignored(signal) {
+			continue;
+		}
+		if !signals.has_handler(signal) {
+			exit_current_process(SIGNAL_EXIT_CODE + signal);
+		}
+		return Some(signal);
+	}
+}
+
+/*
+ * Saves the interrupted state on the user stack and blocks the signal,
+ * until the handler returns. Returns the stack pointer for the handler
+ * or None, if the stack isn´t writable user memory.
+ */
+fn push_signal_frame(thread: &'static Mutex<Thread>, signal: u64, registers: [u64; 16], rip: u64, rflags: u64) -> Option<u64> {
+	let address = (registers[7]
+		.checked_sub(RED_ZONE + core::mem::size_of::<SignalFrame>() as u64)? & !0xf)
+		.checked_sub(0x8)?;
+	let blocked_signals = thread.lock().blocked_signals;
+	let frame = SignalFrame {
+		restorer: thread.process.signals.actions[signal as usize].restorer,
+		signal,
+		blocked_signals,
+		registers,
+		rip,
+		rflags
+	};
+	write_to_user(address, &frame).ok()?;
+	thread.lock().blocked_signals |= 1 << signal;
+	Some(address)
+}
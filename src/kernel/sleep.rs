@@ -0,0 +1,38 @@
+/*
+ * Sleeping threads wait on a single queue. The timer interrupt
+ * wakes them, when the earliest deadline passed, so they sleep
+ * at most one time slice longer than requested.
+ */
+
+use crate::hw::clock;
+use crate::std::WaitQueue;
+use core::sync::atomic::{
+	AtomicU64,
+	Ordering
+};
+
+static SLEEPERS: WaitQueue = WaitQueue::new();
+// Earliest deadline of the sleeping threads in monotonic nanoseconds.
+static NEXT_WAKEUP: AtomicU64 = AtomicU64::new(u64::MAX);
+
+/*
+ * Blocks the current thread for at least the given amount of nanoseconds.
+ */
+pub fn sleep(ns: u64) {
+	let deadline = clock::monotonic_ns().saturating_add(ns);
+	while clock::monotonic_ns() < deadline {
+		NEXT_WAKEUP.fetch_min(deadline, Ordering::SeqCst);
+		// Sleepers with a later deadline register again after a wake up.
+		SLEEPERS.block_until(|| clock::monotonic_ns() >= deadline || NEXT_WAKEUP.load(Ordering::SeqCst) > deadline);
+	}
+}
+
+/*
+ * Called by the timer interrupt of every core.
+ */
+pub fn wake_sleepers() {
+	if clock::monotonic_ns() >= NEXT_WAKEUP.load(Ordering::SeqCst) {
+		NEXT_WAKEUP.store(u64::MAX, Ordering::SeqCst);
+		SLEEPERS.wake_all();
+	}
+}
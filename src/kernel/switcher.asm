@@ -1,40 +1,31 @@
 .text
 .code64
 .global jump_state
-.global load_state
-load_state:
-	mov %rax, 0x0(%rsi)
-	mov %rbx, 0x8(%rsi)
-	mov %rcx, 0x10(%rsi)
-	mov %rdx, 0x18(%rsi)
-	mov %rsi, 0x20(%rsi)
-	mov %rdi, 0x28(%rsi)
-	mov %rbp, 0x30(%rsi)
-	mov %rsp, 0x38(%rsi)
-	add $0x8, 0x38(%rsi)
-	mov %r8, 0x40(%rsi)
-	mov %r9, 0x48(%rsi)
-	mov %r10, 0x50(%rsi)
-	mov %r11, 0x58(%rsi)
-	mov %r12, 0x60(%rsi)
-	mov %r13, 0x68(%rsi)
-	mov %r14, 0x70(%rsi)
-	mov %r15, 0x78(%rsi)
-	sub $0x100, %rsp
-	mov (%rsp), %rax
-	mov %rax, 0x80(%rsi)
-	xor %rax, %rax
+.global switch_context
+# Saves the callee saved registers on the current stack, stores
+# the stack pointer at (%rdi) and continues with the stack in %rsi.
+switch_context:
 	pushf
-	pop 0x88(%rsi)
-	mov %cs, %ax
-	mov %rax, 0x90(%rsi)
-	mov %ds, %ax
-	mov %rax, 0x98(%rsi)
-	rdgsbase %rax
-	mov %rax, 0xa0(%rsi)
+	push %rbx
+	push %rbp
+	push %r12
+	push %r13
+	push %r14
+	push %r15
+	mov %rsp, (%rdi)
+
+	mov %rsi, %rsp
+	pop %r15
+	pop %r14
+	pop %r13
+	pop %r12
+	pop %rbp
+	pop %rbx
+	popf
+	ret
 
 jump_state:
-	mov %rax, 0xa0(%rdi)
+	mov 0xa0(%rdi), %rax
 	wrgsbase %rax
 
 	push 0x98(%rdi)
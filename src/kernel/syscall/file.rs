@@ -1,43 +1,217 @@
 use crate::hw::cpu::syscall::Function;
-use crate::std::{
-	read_file,
-	CustomRAMAllocator,
-	BasicAllocator,
-	PageTableMapper,
-	Box,
-	Allocator
+use abi::syscall;
+use crate::std::read_file;
+use crate::std::mount;
+use crate::std::KernelError;
+use crate::virt::fs;
+use crate::kernel::current_process;
+use crate::kernel::filedescriptor::OpenFile;
+use crate::kernel::usercopy::{
+	copy_to_user,
+	write_to_user
+};
+use super::path_from_user;
+use abi::file::DirectoryRecord;
+use abi::memory::{
+	PROT_READ,
+	PROT_WRITE
 };
-use crate::mm::MappingFlags;
-use crate::virt::fs::FilePath;
-use super::syscallarg_to_string;
-
-const FILE_SYSCALL_METHODS: [Function; 1] = [
-	/*
-	 * Reads file from filesystems (1. arg) at path (2. arg).
-	 * Argument 3 specifies the amount of bytes to be read and
-	 * argument 4 is the offset in the file to read.
-	 * It either returns an error, or the buffer, where the data
-	 * is stored.
-	 */
-	Function {
-		id: 0xd418874d2055fe49,
-		meth: |args| {
-			if let Some(rawstr) = syscallarg_to_string(args[1], args[2]) {
-				let result = read_file(args[0] as usize, FilePath::DOS(rawstr.into()), args[3] as usize, args[4] as usize);
 
-				if let Ok(r#box) = result {
-					let userallocator: CustomRAMAllocator<PageTableMapper> = BasicAllocator::new(PageTableMapper::new(MappingFlags::User));
-					core::mem::ManuallyDrop::new(
-						Box::new_slice_with_alloc(r#box.as_slice(), userallocator)
-					).virtual_address()
-				} else if let Err(err) = result {
-					err as u64
-				} else {
-					unreachable!()
+const FILE_SYSCALL_METHODS: [Function; 17] = [
+	Function {
+		number: syscall::READ_FILE,
+		meth: |args| {
+			let path = path_from_user(args[0], args[1])?;
+			let r#box = read_file(&path, args[2] as usize, args[3] as usize)?;
+			let process = current_process().expect("Syscall without process.");
+			// Empty files get a buffer too, because mappings can´t be empty.
+			let size = r#box.alloc_len().max(1) as u64;
+			let address = process.lock().map_anonymous(None, size, PROT_READ | PROT_WRITE);
+			// The process mustn´t be locked while copying, copy on write faults lock it.
+			let result = address.and_then(|address| copy_to_user(address, r#box.as_slice()).map(|_| address));
+			r#box.free();
+			result
+		}
+	},
+	Function {
+		number: syscall::OPEN,
+		meth: |args| {
+			let path = path_from_user(args[0], args[1])?;
+			let file = OpenFile::open(&path, args[2])?;
+			current_process()
+				.expect("Syscall without process.")
+				.lock()
+				.files
+				.add(file)
+		}
+	},
+	Function {
+		number: syscall::READ,
+		meth: |args| {
+			let file = current_process().expect("Syscall without process.").lock().files.get(args[0])?;
+			Ok(file.read_to_user(args[1], args[2] as usize)? as u64)
+		}
+	},
+	Function {
+		number: syscall::WRITE,
+		meth: |args| {
+			let file = current_process().expect("Syscall without process.").lock().files.get(args[0])?;
+			Ok(file.write_from_user(args[1], args[2] as usize)? as u64)
+		}
+	},
+	Function {
+		number: syscall::LSEEK,
+		meth: |args| {
+			let file = current_process().expect("Syscall without process.").lock().files.get(args[0])?;
+			file.lock().seek(args[1] as i64, args[2])
+		}
+	},
+	Function {
+		number: syscall::CLOSE,
+		meth: |args| {
+			current_process().expect("Syscall without process.").lock().files.close(args[0])?;
+			Ok(0x0)
+		}
+	},
+	Function {
+		number: syscall::FSTAT,
+		meth: |args| {
+			let file = current_process().expect("Syscall without process.").lock().files.get(args[0])?;
+			let stat = file.lock().stat()?;
+			write_to_user(args[1], &stat)?;
+			Ok(0x0)
+		}
+	},
+	Function {
+		number: syscall::PIPE,
+		meth: |args| {
+			let (reader, writer) = OpenFile::pipe();
+			let process = current_process().expect("Syscall without process.");
+			let fds = {
+				let files = &mut process.lock().files;
+				let read_fd = files.add(reader).inspect_err(|_| writer.release())?;
+				let write_fd = files.add(writer).inspect_err(|_| {
+					let _ = files.close(read_fd);
+				})?;
+				[read_fd, write_fd]
+			};
+			if let Err(error) = write_to_user(args[0], &fds) {
+				let files = &mut process.lock().files;
+				for fd in fds {
+					let _ = files.close(fd);
 				}
-			} else {
-				0xfff
+				return Err(error);
+			}
+			Ok(0x0)
+		}
+	},
+	Function {
+		number: syscall::MKDIR,
+		meth: |args| {
+			let path = path_from_user(args[0], args[1])?;
+			if fs::is_mount_point(&path) {
+				return Err(KernelError::AlreadyExists);
+			}
+			let (filesystem, path) = fs::resolve(&path)?;
+			fs::filesystem(filesystem)?.create(path, true)?;
+			Ok(0x0)
+		}
+	},
+	Function {
+		number: syscall::UNLINK,
+		meth: |args| {
+			let (filesystem, path) = fs::resolve(&path_from_user(args[0], args[1])?)?;
+			let mut filesystem = fs::filesystem(filesystem)?;
+			if filesystem.stat(path.clone())?.is_directory {
+				return Err(KernelError::IsDirectory);
+			}
+			filesystem.remove(path)?;
+			Ok(0x0)
+		}
+	},
+	Function {
+		number: syscall::RMDIR,
+		meth: |args| {
+			let path = path_from_user(args[0], args[1])?;
+			if fs::is_mount_point(&path) {
+				return Err(KernelError::Busy);
+			}
+			let (filesystem, path) = fs::resolve(&path)?;
+			let mut filesystem = fs::filesystem(filesystem)?;
+			if !filesystem.stat(path.clone())?.is_directory {
+				return Err(KernelError::NotDirectory);
+			}
+			filesystem.remove(path)?;
+			Ok(0x0)
+		}
+	},
+	Function {
+		number: syscall::FTRUNCATE,
+		meth: |args| {
+			let file = current_process().expect("Syscall without process.").lock().files.get(args[0])?;
+			file.lock().truncate(args[1])?;
+			Ok(0x0)
+		}
+	},
+	Function {
+		number: syscall::STAT,
+		meth: |args| {
+			let (filesystem, path) = fs::resolve(&path_from_user(args[0], args[1])?)?;
+			let stat = fs::filesystem(filesystem)?.stat(path)?.stat();
+			write_to_user(args[2], &stat)?;
+			Ok(0x0)
+		}
+	},
+	Function {
+		number: syscall::READDIR,
+		meth: |args| {
+			let (filesystem, path) = fs::resolve(&path_from_user(args[0], args[1])?)?;
+			// The filesystem mustn´t be locked while copying, copy on write faults may block.
+			let items = fs::filesystem(filesystem)?.readdir(path)?;
+			let mut written: u64 = 0;
+			for item in (&items).into_iter().skip(args[4] as usize).take(args[3] as usize) {
+				let record = DirectoryRecord::new(item.name.bytes(), item.info.stat());
+				let address = written.checked_mul(size_of::<DirectoryRecord>() as u64)
+					.and_then(|offset| args[2].checked_add(offset))
+					.ok_or(KernelError::BadAddress)?;
+				write_to_user(address, &record)?;
+				written += 1;
+			}
+			Ok(written)
+		}
+	},
+	Function {
+		number: syscall::CHDIR,
+		meth: |args| {
+			let path = path_from_user(args[0], args[1])?;
+			let (filesystem, file_path) = fs::resolve(&path)?;
+			if !fs::filesystem(filesystem)?.stat(file_path)?.is_directory {
+				return Err(KernelError::NotDirectory);
+			}
+			current_process().expect("Syscall without process.").lock().working_directory = path;
+			Ok(0x0)
+		}
+	},
+	Function {
+		number: syscall::GETCWD,
+		meth: |args| {
+			let working_directory = current_process().expect("Syscall without process.").lock().working_directory.clone();
+			if working_directory.len() as u64 <= args[1] {
+				copy_to_user(args[0], working_directory.bytes())?;
+			}
+			Ok(working_directory.len() as u64)
+		}
+	},
+	Function {
+		number: syscall::MOUNT,
+		meth: |args| {
+			// Mounts change the files of every process.
+			if !current_process().expect("Syscall without process.").lock().is_privileged() {
+				return Err(KernelError::NotPermitted);
 			}
+			let path = path_from_user(args[1], args[2])?;
+			mount(args[0] as usize, &path)?;
+			Ok(0x0)
 		}
 	}
 ];
@@ -0,0 +1,50 @@
+use crate::hw::cpu::syscall::Function;
+use abi::syscall;
+use crate::kernel::port::Port;
+use crate::kernel::usercopy::string_from_user;
+
+const IPC_SYSCALL_METHODS: [Function; 7] = [
+	Function {
+		number: syscall::PORT_CREATE,
+		meth: |args| Port::create(string_from_user(args[0], args[1])?)
+	},
+	Function {
+		number: syscall::PORT_LOOKUP,
+		meth: |args| Port::lookup(string_from_user(args[0], args[1])?.as_str())
+	},
+	Function {
+		number: syscall::PORT_DESTROY,
+		meth: |args| {
+			Port::destroy_owned(args[0])?;
+			Ok(0x0)
+		}
+	},
+	Function {
+		number: syscall::PORT_SEND,
+		meth: |args| {
+			Port::send(args[0], args[1], args[2] as usize)?;
+			Ok(0x0)
+		}
+	},
+	Function {
+		number: syscall::PORT_CALL,
+		meth: |args| Ok(Port::call(args[0], args[1], args[2] as usize, args[3], args[4] as usize)? as u64)
+	},
+	Function {
+		number: syscall::PORT_RECEIVE,
+		meth: |args| Ok(Port::receive(args[0], args[1], args[2] as usize, args[3])? as u64)
+	},
+	Function {
+		number: syscall::PORT_REPLY,
+		meth: |args| {
+			Port::reply(args[0], args[1], args[2], args[3] as usize)?;
+			Ok(0x0)
+		}
+	}
+];
+
+pub fn setup() {
+	for meth in IPC_SYSCALL_METHODS {
+		meth.add();
+	}
+}
@@ -1,37 +1,107 @@
 use crate::hw::cpu::syscall::Function;
-use crate::std::{
-	CustomRAMAllocator,
-	BasicAllocator,
-	PageTableMapper,
-	Box,
-	Allocator,
-	LazyMutex
+use abi::syscall;
+use crate::std::KernelError;
+use crate::kernel::current_process;
+use crate::kernel::filedescriptor::OpenFile;
+use crate::kernel::usercopy::string_from_user;
+use abi::file::OPEN_WRITE;
+use abi::memory::{
+	MAP_WRITE,
+	PROT_READ,
+	PROT_WRITE
 };
-use crate::mm::MappingFlags;
 
-const USER_ALLOCATOR: LazyMutex<CustomRAMAllocator<PageTableMapper>> =
-	LazyMutex::new(|| BasicAllocator::new(PageTableMapper::new(MappingFlags::User)));
-
-const MEM_SYSCALL_METHODS: [Function; 2] = [
-	/*
-	 * Allocates X bytes user memory, where X is the first argument.
-	 * Returns the virtual address of the new memory
-	 */
+const MEM_SYSCALL_METHODS: [Function; 9] = [
+	Function {
+		number: syscall::ALLOCATE,
+		meth: |args| {
+			current_process()
+				.expect("Syscall without process.")
+				.lock()
+				.map_anonymous(None, args[0], PROT_READ | PROT_WRITE)
+		}
+	},
+	Function {
+		number: syscall::FREE,
+		meth: |args| {
+			current_process().expect("Syscall without process.").lock().unmap(args[0], args[1])?;
+			Ok(0x0)
+		}
+	},
+	Function {
+		number: syscall::SHM_CREATE,
+		meth: |args| {
+			let name = if args[2] == 0 {
+				None
+			} else {
+				Some(string_from_user(args[1], args[2])?)
+			};
+			let file = OpenFile::create_shared_memory(args[0], name)?;
+			current_process()
+				.expect("Syscall without process.")
+				.lock()
+				.files
+				.add(file)
+		}
+	},
+	Function {
+		number: syscall::SHM_OPEN,
+		meth: |args| {
+			let name = string_from_user(args[0], args[1])?;
+			let file = OpenFile::open_shared_memory(name.as_str(), args[2])?;
+			current_process()
+				.expect("Syscall without process.")
+				.lock()
+				.files
+				.add(file)
+		}
+	},
+	Function {
+		number: syscall::SHM_MAP,
+		meth: |args| {
+			if args[2] & !MAP_WRITE != 0 {
+				return Err(KernelError::InvalidArgument);
+			}
+			let writable = args[2] & MAP_WRITE != 0;
+			// The process stays locked, so the descriptor can´t be closed meanwhile.
+			let mut process = current_process().expect("Syscall without process.").lock();
+			let (memory, flags) = process.files.get(args[0])?.lock().shared_memory()?;
+			if writable && flags & OPEN_WRITE == 0 {
+				return Err(KernelError::NotPermitted);
+			}
+			let address = if args[1] == 0 { None } else { Some(args[1]) };
+			process.map_shared_memory(memory, address, writable)
+		}
+	},
+	Function {
+		number: syscall::SHM_UNMAP,
+		meth: |args| {
+			current_process().expect("Syscall without process.").lock().unmap_shared_memory(args[0])?;
+			Ok(0x0)
+		}
+	},
+	Function {
+		number: syscall::MMAP,
+		meth: |args| {
+			let address = if args[0] == 0 { None } else { Some(args[0]) };
+			current_process()
+				.expect("Syscall without process.")
+				.lock()
+				.map_anonymous(address, args[1], args[2])
+		}
+	},
 	Function {
-		id: 0x5f574e0f2ba82e47,
+		number: syscall::MUNMAP,
 		meth: |args| {
-			USER_ALLOCATOR.lock().allocate::<u8>(args[0] as usize).unwrap() as u64
+			current_process().expect("Syscall without process.").lock().unmap(args[0], args[1])?;
+			Ok(0x0)
 		}
 	},
-	/*
-	 * Frees user memory. The first argument specifies the virtual
-	 * address and the second one the size of the allocation.
-	 */
 	Function {
-		id: 0x182e8b4510a7eb40,
+		number: syscall::MPROTECT,
 		meth: |args| {
-			USER_ALLOCATOR.lock().free(args[0] as *const u8, args[1] as usize);
-			0x0
+			current_process().expect("Syscall without process.").lock().protect(args[0], args[1], args[2])?;
+			Ok(0x0)
 		}
 	}
 ];
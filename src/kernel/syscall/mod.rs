@@ -1,66 +1,125 @@
+/*
+ * Syscall handlers. Numbers, arguments and return values
+ * are defined by the abi crate, which user programs use too.
+ */
 mod graphic;
 mod file;
 mod mem;
+mod process;
+mod thread;
+mod signal;
+mod time;
+mod ipc;
 
 use crate::hw::cpu::syscall::Function;
+use abi::syscall;
 use crate::print;
 use core::fmt::Write;
 use crate::std::{
-	String,
 	elf::load_elf_from_file,
-	FilePath
+	elf::ProgramArguments,
+	String
+};
+use crate::virt::fs;
+use abi::process::ExecArguments;
+use abi::file::{
+	STDIO_AMOUNT,
+	NO_FILE
+};
+use crate::std::{
+	KernelError,
+	KernelResult
+};
+use super::FileDescriptors;
+use super::current_process;
+use super::usercopy::{
+	string_from_user,
+	strings_from_user,
+	read_from_user
 };
 
 const SYSCALL_METHODS: [Function; 5] = [
-	/*
-	 * Exits current process
-	 * TODO: Status code support
-	 */
 	Function {
-		id: 0xa4998996a6277317,
-		meth: |_| crate::std::exit()
+		number: syscall::EXIT,
+		meth: |args| crate::kernel::exit_current_process(args[0])
 	},
-	/*
-	 * Powers off the PC.
-	 */
 	Function {
-		id: 0x4a33e7eb45595ceb,
+		number: syscall::SHUTDOWN,
 		meth: |_| crate::hw::power::shutdown()
 	},
-	/*
-	 * Reboots the pc
-	 */
 	Function {
-		id: 0xba3f7ec4fdf5556b,
+		number: syscall::REBOOT,
 		meth: |_| crate::hw::power::reboot()
 	},
-	/*
-	 * Prints text from string pointer (first argument) and
-	 * length (second argument).
-	 */
 	Function {
-		id: 0x588f73f96a7de691,
-		meth: |args| if let Some(decoded_str) = syscallarg_to_string(args[0], args[1]) {
-			print!("{}", decoded_str);
-			0x0
-		} else {
-			0x2
+		number: syscall::PRINT,
+		meth: |args| {
+			print!("{}", string_from_user(args[0], args[1])?);
+			Ok(0x0)
 		}
 	},
-	/*
-	 * Execute elf at DOS Path (string pointer(first argument) and
-	 * length (second argument)).
-	 */
 	Function {
-		id: 0xed24224fa1bde4,
-		meth: |args| if let Some(decoded_str) = syscallarg_to_string(args[1], args[2]) {
-			load_elf_from_file(args[0] as usize, FilePath::Unix(decoded_str.into())) as u64
-		} else {
-			0x0
+		number: syscall::EXEC,
+		meth: |args| {
+			let path = path_from_user(args[0], args[1])?;
+			let mut arguments = ProgramArguments::new();
+			if args[2] != 0x0 {
+				let exec_arguments = read_from_user::<ExecArguments>(args[2])?;
+				arguments.arguments = strings_from_user(exec_arguments.arguments, exec_arguments.argument_amount)?;
+				arguments.environment = strings_from_user(exec_arguments.environment, exec_arguments.environment_amount)?;
+			}
+			if args[4] > STDIO_AMOUNT {
+				return Err(KernelError::InvalidArgument);
+			}
+			let mut parent_fds = [NO_FILE; STDIO_AMOUNT as usize];
+			for fd in 0..args[4] {
+				parent_fds[fd as usize] = read_from_user::<u64>(args[3].wrapping_add(fd * 8))?;
+			}
+			let mut files = stdio_files(&parent_fds)?;
+			let result = load_elf_from_file(&path, &arguments, &files);
+			// The program got its own references to the files.
+			files.close_all();
+			result
 		}
 	}
 ];
 
+/*
+ * Absolute path of the path in user memory. Relative paths
+ * start at the working directory of the current process.
+ */
+fn path_from_user(address: u64, length: u64) -> KernelResult<String> {
+	let path = string_from_user(address, length)?;
+	if path.is_empty() {
+		return Err(KernelError::NotFound);
+	}
+	let process = current_process().expect("Syscall without process.");
+	let working_directory = process.lock().working_directory.clone();
+	Ok(fs::absolute_path(&working_directory, &path))
+}
+
+/*
+ * Descriptors for a program started by exec. Entries of the array
+ * are descriptors of the current process or NO_FILE.
+ */
+fn stdio_files(parent_fds: &[u64]) -> KernelResult<FileDescriptors> {
+	let process = current_process().expect("Syscall without process.");
+	let mut files = FileDescriptors::new();
+	for (fd, parent_fd) in parent_fds.iter().enumerate() {
+		if *parent_fd == NO_FILE {
+			continue;
+		}
+		match process.lock().files.get(*parent_fd) {
+			Ok(file) => files.inherit(fd as u64, file),
+			Err(error) => {
+				files.close_all();
+				return Err(error);
+			}
+		}
+	}
+	Ok(files)
+}
+
 /*
  * Add all syscall functions.
  */
@@ -71,19 +130,9 @@ pub fn setup() {
 	graphic::setup();
 	file::setup();
 	mem::setup();
-}
-
-/*
- * Helper method of converting two arguments into
- * a str pointer
- */
-pub fn syscallarg_to_string(ptr: u64, size: u64) -> Option<&'static str> {
-	str::from_utf8(
-		unsafe {
-			core::slice::from_raw_parts(
-				ptr as *mut u8,
-				size as usize
-			)
-		}
-	).ok()
+	process::setup();
+	thread::setup();
+	signal::setup();
+	time::setup();
+	ipc::setup();
 }
@@ -0,0 +1,72 @@
+use crate::hw::cpu::syscall::Function;
+use abi::syscall;
+use crate::std::{
+	VecBase,
+	KernelError
+};
+use crate::kernel::{
+	wait_for_child,
+	fork_current_process,
+	current_process,
+	process_table
+};
+use crate::kernel::usercopy::write_to_user;
+use abi::process::ProcessRecord;
+
+const PROCESS_SYSCALL_METHODS: [Function; 5] = [
+	Function {
+		number: syscall::WAIT,
+		meth: |args| {
+			let (pid, exit_code) = wait_for_child(args[0]).ok_or(KernelError::NoChild)?;
+			if args[1] != 0 {
+				write_to_user(args[1], &exit_code)?;
+			}
+			Ok(pid)
+		}
+	},
+	Function {
+		number: syscall::FORK,
+		meth: |_| fork_current_process().ok_or(KernelError::OutOfMemory)
+	},
+	Function {
+		number: syscall::SET_AFFINITY,
+		meth: |args| {
+			current_process().expect("Attempt to do syscalls in early init task.").set_affinity(args[0])?;
+			Ok(0x0)
+		}
+	},
+	Function {
+		number: syscall::AFFINITY,
+		meth: |_| Ok(current_process().expect("Attempt to do syscalls in early init task.").affinity())
+	},
+	Function {
+		number: syscall::PROCESS_LIST,
+		meth: |args| {
+			let table = process_table();
+			for (idx, info) in (&table).into_iter().take(args[1] as usize).enumerate() {
+				let record = ProcessRecord {
+					pid: info.pid,
+					parent: info.parent,
+					r#type: info.r#type as u64,
+					state: info.state as u64,
+					flags: info.flags,
+					threads: info.threads,
+					cpu_time: info.cpu_time,
+					mapped_memory: info.mapped_memory
+				};
+				let address = (idx as u64)
+					.checked_mul(core::mem::size_of::<ProcessRecord>() as u64)
+					.and_then(|offset| args[0].checked_add(offset))
+					.ok_or(KernelError::BadAddress)?;
+				write_to_user(address, &record)?;
+			}
+			Ok(table.len() as u64)
+		}
+	}
+];
+
+pub fn setup() {
+	for meth in PROCESS_SYSCALL_METHODS {
+		meth.add();
+	}
+}
@@ -0,0 +1,42 @@
+use crate::hw::cpu::syscall::Function;
+use abi::syscall;
+use crate::kernel::{
+	current_process,
+	send_signal,
+	return_from_signal,
+	SignalAction
+};
+use crate::std::KernelError;
+
+const SIGNAL_SYSCALL_METHODS: [Function; 3] = [
+	Function {
+		number: syscall::KILL,
+		meth: |args| {
+			send_signal(args[0], args[1])?;
+			Ok(0x0)
+		}
+	},
+	Function {
+		number: syscall::SIGACTION,
+		meth: |args| current_process()
+			.expect("Attempt to do syscalls in early init task.")
+			.lock()
+			.signals
+			.set_action(args[0], SignalAction {
+				handler: args[1],
+				restorer: args[2]
+			})
+			.map(|previous| previous.handler)
+			.ok_or(KernelError::InvalidArgument)
+	},
+	Function {
+		number: syscall::SIGRETURN,
+		meth: |_| return_from_signal()
+	}
+];
+
+pub fn setup() {
+	for meth in SIGNAL_SYSCALL_METHODS {
+		meth.add();
+	}
+}
@@ -0,0 +1,65 @@
+use crate::hw::cpu::syscall::Function;
+use abi::syscall;
+use crate::kernel::{
+	current_process,
+	current_thread,
+	exit_current_thread,
+	join_thread,
+	ProcessPriority
+};
+use crate::std::KernelError;
+use crate::kernel::usercopy::write_to_user;
+
+const THREAD_SYSCALL_METHODS: [Function; 5] = [
+	Function {
+		number: syscall::SET_PRIORITY,
+		meth: |args| {
+			let priority = enum_iterator::all::<ProcessPriority>()
+				.nth(args[0] as usize)
+				.ok_or(KernelError::InvalidArgument)?;
+			let thread = current_thread().expect("Syscall without thread.");
+			let current_priority = thread.lock().priority();
+			// Realtime threads starve the others, so only privileged processes raise threads to it.
+			if priority == ProcessPriority::REALTIME && current_priority != ProcessPriority::REALTIME
+				&& !thread.process.lock().is_privileged() {
+				return Err(KernelError::NotPermitted);
+			}
+			thread.lock().set_priority(priority);
+			Ok(0x0)
+		}
+	},
+	Function {
+		number: syscall::PRIORITY,
+		meth: |_| Ok(current_thread()
+			.expect("Syscall without thread.")
+			.lock()
+			.priority() as u64)
+	},
+	Function {
+		number: syscall::CREATE_THREAD,
+		meth: |args| current_process()
+			.expect("Syscall without process.")
+			.create_thread(args[0], args[1], args[2])
+			.ok_or(KernelError::NoProcess)
+	},
+	Function {
+		number: syscall::EXIT_THREAD,
+		meth: |args| exit_current_thread(args[0])
+	},
+	Function {
+		number: syscall::JOIN_THREAD,
+		meth: |args| {
+			let exit_code = join_thread(args[0]).ok_or(KernelError::NoProcess)?;
+			if args[1] != 0 {
+				write_to_user(args[1], &exit_code)?;
+			}
+			Ok(0x0)
+		}
+	}
+];
+
+pub fn setup() {
+	for meth in THREAD_SYSCALL_METHODS {
+		meth.add();
+	}
+}
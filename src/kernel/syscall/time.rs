@@ -0,0 +1,33 @@
+use crate::hw::cpu::syscall::Function;
+use abi::syscall;
+use crate::hw::clock;
+use abi::clock::Timespec;
+use crate::kernel::sleep;
+use crate::kernel::usercopy::write_to_user;
+use crate::std::KernelError;
+
+const TIME_SYSCALL_METHODS: [Function; 2] = [
+	Function {
+		number: syscall::SLEEP,
+		meth: |args| {
+			sleep(args[0]);
+			Ok(0x0)
+		}
+	},
+	Function {
+		number: syscall::CLOCK_GETTIME,
+		meth: |args| {
+			let time = clock::clock_time(args[0]).ok_or(KernelError::InvalidArgument)?;
+			if args[1] != 0 {
+				write_to_user(args[1], &Timespec::from_ns(time))?;
+			}
+			Ok(time)
+		}
+	}
+];
+
+pub fn setup() {
+	for meth in TIME_SYSCALL_METHODS {
+		meth.add();
+	}
+}
@@ -0,0 +1,245 @@
+/*
+ * Threads are scheduled instead of processes. Every thread has
+ * its own task state and stacks, but runs in the address space
+ * of its process.
+ */
+
+use super::scheduler::{
+	self,
+	Process,
+	ProcessPriority,
+	TaskState,
+	RipCast
+};
+use crate::std::{
+	Mutex,
+	WaitQueue,
+	Vec,
+	VecBase,
+	Box
+};
+use core::{
+	ops::Index,
+	sync::atomic::{
+		AtomicBool,
+		AtomicU64,
+		Ordering
+	}
+};
+
+/*
+ * BLOCKED threads wait on a wait queue and aren´t queued to run.
+ * KILLED threads may still run on their kernel stack, until the
+ * switch away from them finished. Then they´re ZOMBIEs, until
+ * they´re reaped. REAPED slots are reused for new threads.
+ */
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ThreadState {
+	RUNNING,
+	IDLE,
+	BLOCKED,
+	KILLED,
+	ZOMBIE,
+	REAPED
+}
+
+pub struct Thread {
+	pub(super) task_state: TaskState,
+	/*
+	 * Used for interrupts in user mode, syscalls and switching.
+	 * While the thread isn´t running, stack_pointer points to
+	 * the saved registers on it.
+	 */
+	kernel_stack: Box<[u8]>,
+	pub(super) stack_pointer: u64,
+	// Set, until the switch away from the thread finished.
+	pub(super) on_cpu: bool,
+	// Only set, when the kernel allocated the stack.
+	stack: Box<[u8]>,
+	pub(super) state: ThreadState,
+	priority: ProcessPriority,
+	/*
+	 * Wait queue the thread is blocked on. Waking doesn´t lock
+	 * the thread, it only sets wakeup.
+	 */
+	pub(super) wait_channel: AtomicU64,
+	pub(super) wakeup: AtomicBool,
+	// Signals, which aren´t delivered to the thread. Set, while handling them.
+	pub(super) blocked_signals: u64,
+	exit_code: u64,
+
+	pub tid: u64,
+	pub process: &'static Mutex<Process>
+}
+
+const KERNEL_STACK_SIZE: usize = 0x10000;
+
+static THREADS: Mutex<Vec<Mutex<Thread>>> = Mutex::new(Vec::new());
+// Woken, whenever a thread became a zombie.
+pub(super) static EXITED_THREADS: WaitQueue = WaitQueue::new();
+
+impl Thread {
+	pub fn new(process: &'static Mutex<Process>, task_state: TaskState, stack: Box<[u8]>, priority: ProcessPriority) -> Thread {
+		let mut thread = Thread {
+			task_state,
+			kernel_stack: Box::new_sized(KERNEL_STACK_SIZE),
+			stack_pointer: 0x0,
+			on_cpu: false,
+			stack,
+			state: ThreadState::IDLE,
+			priority,
+			wait_channel: AtomicU64::new(0),
+			wakeup: AtomicBool::new(false),
+			blocked_signals: 0,
+			exit_code: 0,
+			tid: task_state.uid,
+			process
+		};
+		thread.prepare_kernel_stack();
+		thread
+	}
+
+	/*
+	 * Adds the thread to the scheduler and returns its tid.
+	 * The slot of a reaped thread is reused, if there´s one.
+	 * The thread has to be counted in live_threads of its process already.
+	 */
+	pub fn spawn(self) -> u64 {
+		let tid = self.tid;
+		let priority = self.priority;
+		let thread = {
+			let mut threads = THREADS.lock();
+			if let Some(slot) = THREADS.read().into_iter().find(|slot| slot.lock().state == ThreadState::REAPED) {
+				*slot.lock() = self;
+				slot
+			} else {
+				threads.push_back(Mutex::new(self));
+				THREADS.read().index(threads.len() - 1)
+			}
+		};
+		scheduler::enqueue(thread, priority);
+		tid
+	}
+
+	pub fn from_tid(tid: u64) -> Option<&'static Mutex<Thread>> {
+		let threads = THREADS.read();
+		Some(
+			THREADS.read().index(
+				// Not locking, the scheduler looks up threads while others hold their locks.
+				threads.into_iter().position(|t| {
+					let thread = t.read();
+					thread.tid == tid && thread.state != ThreadState::REAPED
+				})?
+			)
+		)
+	}
+
+	pub fn is_locked() -> bool {
+		THREADS.is_locked()
+	}
+
+	/*
+	 * Places the registers switch_context restores on the kernel stack.
+	 * The first switch to the thread returns into thread_entry.
+	 */
+	fn prepare_kernel_stack(&mut self) {
+		let frame: [u64; 9] = [
+			0x0, // R15
+			0x0, // R14
+			0x0, // R13
+			0x0, // R12
+			0x0, // RBP
+			0x0, // RBX
+			0x2, // RFLAGS
+			(scheduler::thread_entry as fn() -> !).ripcast(),
+			0x0 // Alignment
+		];
+		self.stack_pointer = self.kernel_stack_top() - core::mem::size_of_val(&frame) as u64;
+		unsafe {
+			core::ptr::copy_nonoverlapping(frame.as_ptr(), self.stack_pointer as *mut u64, frame.len());
+		}
+	}
+
+	pub(super) fn kernel_stack_top(&self) -> u64 {
+		self.kernel_stack.as_stack() as u64
+	}
+
+	pub fn priority(&self) -> ProcessPriority {
+		self.priority
+	}
+
+	/*
+	 * Changes the scheduling class. A running thread is
+	 * queued with its new priority, when it´s switched out.
+	 */
+	pub fn set_priority(&mut self, priority: ProcessPriority) -> &mut Self {
+		self.priority = priority;
+		self
+	}
+
+	pub fn is_alive(&self) -> bool {
+		!matches!(self.state, ThreadState::KILLED | ThreadState::ZOMBIE | ThreadState::REAPED)
+	}
+
+	/*
+	 * Stops the thread with the exit code. A thread, which is still
+	 * on a core, becomes a zombie after it was switched out.
+	 */
+	pub(super) fn terminate(&mut self, exit_code: u64) {
+		if !self.is_alive() {
+			return;
+		}
+		self.exit_code = exit_code;
+		if self.on_cpu {
+			self.state = ThreadState::KILLED;
+		} else {
+			self.set_zombie();
+		}
+	}
+
+	/*
+	 * Nothing runs on the stacks of the thread anymore, so it can be reaped.
+	 * The process becomes a zombie with its last thread.
+	 */
+	pub(super) fn set_zombie(&mut self) {
+		self.state = ThreadState::ZOMBIE;
+		self.process.live_threads.fetch_sub(1, Ordering::SeqCst);
+		EXITED_THREADS.wake_all();
+	}
+
+	/*
+	 * Marks a blocked thread, that was woken, as idle. Returns true,
+	 * if the caller has to queue it. Threads still being switched
+	 * out are queued by finish_switch.
+	 */
+	pub(super) fn wake(&mut self) -> bool {
+		self.wakeup.store(false, Ordering::SeqCst);
+		if self.state == ThreadState::BLOCKED {
+			self.state = ThreadState::IDLE;
+			!self.on_cpu
+		} else {
+			false
+		}
+	}
+
+	/*
+	 * Frees the stacks of a zombie and returns its exit code.
+	 */
+	pub(super) fn reap(&mut self) -> u64 {
+		assert!(self.state == ThreadState::ZOMBIE, "Attempt to reap thread, which didn´t exit.");
+		core::mem::replace(&mut self.stack, Box::null()).free();
+		core::mem::replace(&mut self.kernel_stack, Box::null()).free();
+		self.state = ThreadState::REAPED;
+		self.exit_code
+	}
+}
+
+unsafe impl Sync for Thread {}
+
+/*
+ * All thread slots. Threads are never removed,
+ * so the references stay valid.
+ */
+pub(super) fn threads() -> &'static Vec<Mutex<Thread>> {
+	THREADS.read()
+}
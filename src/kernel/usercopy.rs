@@ -0,0 +1,170 @@
+/*
+ * Access to memory of the current process. Syscalls mustn´t use
+ * pointers from user mode directly, because they may point to
+ * kernel memory or to nothing. The memory is checked page by page
+ * with the page table locked, so it can´t be unmapped meanwhile.
+ */
+
+use super::scheduler::current_process;
+use crate::mm::MappingFlags;
+use abi::process::StringRef;
+use crate::std::{
+	String,
+	Vec,
+	KernelError,
+	KernelResult
+};
+
+pub const USER_ADDRESS_LIMIT: u64 = 0x800000000000;
+// Page entry flag, which allows writing.
+const WRITABLE: u64 = 0x2;
+// Longer strings are rejected, so users can´t exhaust kernel memory.
+const MAX_STRING_LENGTH: usize = 0x100000;
+// Longer string lists are rejected for the same reason.
+const MAX_STRING_AMOUNT: u64 = 0x400;
+
+/*
+ * Copies user memory at the address to the buffer. Returns BadAddress,
+ * if any byte isn´t mapped for the user.
+ */
+pub fn copy_from_user(buffer: &mut [u8], address: u64) -> KernelResult<()> {
+	let process = current_process().ok_or(KernelError::BadAddress)?;
+	let mut copied = 0;
+	while copied < buffer.len() {
+		let current = user_address(address, copied)?;
+		let page_table = process.page_table.lock();
+		let page_size = page_table.mapped_page_size(current, MappingFlags::User as u64).ok_or(KernelError::BadAddress)?;
+		let amount = chunk_size(current, page_size, buffer.len() - copied);
+		unsafe {
+			core::ptr::copy_nonoverlapping(current as *const u8, buffer[copied..].as_mut_ptr(), amount);
+		}
+		drop(page_table);
+		copied += amount;
+	}
+	Ok(())
+}
+
+/*
+ * Copies the content to user memory at the address. Copy on write
+ * pages are copied first, because the kernel can´t write to them either.
+ * Returns BadAddress, if any byte isn´t writable for the user.
+ */
+pub fn copy_to_user(address: u64, content: &[u8]) -> KernelResult<()> {
+	let process = current_process().ok_or(KernelError::BadAddress)?;
+	let mut copied = 0;
+	while copied < content.len() {
+		let current = user_address(address, copied)?;
+		let page_table = process.page_table.lock();
+		let Some(page_size) = page_table.mapped_page_size(current, MappingFlags::User as u64 | WRITABLE) else {
+			page_table.mapped_page_size(current, MappingFlags::User as u64).ok_or(KernelError::BadAddress)?;
+			drop(page_table);
+			if !process.lock().resolve_copy_on_write(current) {
+				return Err(KernelError::BadAddress);
+			}
+			continue;
+		};
+		let amount = chunk_size(current, page_size, content.len() - copied);
+		unsafe {
+			core::ptr::copy_nonoverlapping(content[copied..].as_ptr(), current as *mut u8, amount);
+		}
+		drop(page_table);
+		copied += amount;
+	}
+	Ok(())
+}
+
+/*
+ * Reads a value from user memory. Only use it for
+ * types, which are valid for any content.
+ */
+pub fn read_from_user<T: Copy>(address: u64) -> KernelResult<T> {
+	let mut value = core::mem::MaybeUninit::<T>::zeroed();
+	copy_from_user(
+		unsafe {
+			core::slice::from_raw_parts_mut(value.as_mut_ptr() as *mut u8, core::mem::size_of::<T>())
+		},
+		address
+	)?;
+	Ok(unsafe {
+		value.assume_init()
+	})
+}
+
+pub fn write_to_user<T: Copy>(address: u64, value: &T) -> KernelResult<()> {
+	copy_to_user(
+		address,
+		unsafe {
+			core::slice::from_raw_parts(value as *const T as *const u8, core::mem::size_of::<T>())
+		}
+	)
+}
+
+/*
+ * Copies an UTF-8 string with the length in bytes from user memory.
+ */
+pub fn string_from_user(address: u64, length: u64) -> KernelResult<String> {
+	if length == 0 {
+		return Ok(String::new());
+	}
+	if length as usize > MAX_STRING_LENGTH {
+		return Err(KernelError::InvalidArgument);
+	}
+	let mut string = unsafe {
+		String::new_uninit(length as usize)
+	};
+	copy_from_user(string.bytes_mut(), address)?;
+	core::str::from_utf8(string.bytes()).map_err(|_| KernelError::InvalidArgument)?;
+	Ok(string)
+}
+
+/*
+ * Copies an array of StringRefs with the amount of entries and their
+ * strings from user memory. Returns ArgumentListTooLong for long arrays.
+ */
+pub fn strings_from_user(address: u64, amount: u64) -> KernelResult<Vec<String>> {
+	if amount > MAX_STRING_AMOUNT {
+		return Err(KernelError::ArgumentListTooLong);
+	}
+	let mut strings = Vec::new();
+	for idx in 0..amount {
+		let entry_address = address
+			.checked_add(idx * core::mem::size_of::<StringRef>() as u64)
+			.ok_or(KernelError::BadAddress)?;
+		let string = read_from_user::<StringRef>(entry_address)?;
+		strings.push_back(string_from_user(string.address, string.length)?);
+	}
+	Ok(strings)
+}
+
+/*
+ * Checks, that the whole range is mapped for the user.
+ */
+pub fn is_user_range(address: u64, length: u64) -> bool {
+	let Some(process) = current_process() else {
+		return false;
+	};
+	let mut checked = 0;
+	while checked < length as usize {
+		let Ok(current) = user_address(address, checked) else {
+			return false;
+		};
+		let Some(page_size) = process.page_table.lock().mapped_page_size(current, MappingFlags::User as u64) else {
+			return false;
+		};
+		checked += chunk_size(current, page_size, length as usize - checked);
+	}
+	true
+}
+
+fn user_address(address: u64, offset: usize) -> KernelResult<u64> {
+	address.checked_add(offset as u64)
+		.filter(|current| *current < USER_ADDRESS_LIMIT)
+		.ok_or(KernelError::BadAddress)
+}
+
+/*
+ * Bytes until the end of the page or the end of the copy.
+ */
+fn chunk_size(address: u64, page_size: usize, remaining: usize) -> usize {
+	(page_size - (address as usize & (page_size - 1))).min(remaining)
+}
@@ -85,13 +85,18 @@ fn main() -> Status {
 						 "lidt [{0}]",
 						 "mov rax, 0x10676",
 						 "mov cr4, rax",
+						 "mov rax, cr0",
+						 "or rax, 0x10000",
+						 "mov cr0, rax",
 						 "wrgsbase {1:r}",
 						 in(reg) idtr.as_ptr(),
-						 in(reg) 0);
+						 in(reg) 0,
+						 out("rax") _);
 	}
 	*UEFI_RESULT.lock() = Some(uefi_result);
 	mm::setup(memory_map);
 	hw::cpu::setup1();
+	hw::clock::setup();
 
 	log::debug!("Setting up boot setup process.");
 	Process::spawn_init_process(boot_core_setup as fn() -> !)
@@ -113,6 +118,7 @@ fn boot_core_setup() -> ! {
 
 	loop {
 		std::r#yield();
+		std::hlt();
 	}
 }
 
@@ -2,7 +2,8 @@ use uefi::mem::memory_map::*;
 use uefi;
 use crate::std::{
 	StackVec,
-	Mutex
+	Mutex,
+	KernelError
 };
 use crate::mm::align_size;
 
@@ -13,6 +14,15 @@ pub enum BuddyError {
 	UnknownSize
 }
 
+impl From<BuddyError> for KernelError {
+	fn from(error: BuddyError) -> KernelError {
+		match error {
+			BuddyError::_1gIndexFault | BuddyError::_2mIndexFault => KernelError::OutOfMemory,
+			BuddyError::UnknownSize => KernelError::InvalidArgument
+		}
+	}
+}
+
 #[derive(Copy, Clone)]
 pub struct Buddy {
 	buddies: [u64; 8],
@@ -2,6 +2,7 @@ mod pageentry;
 mod pagedirectory;
 mod mapped;
 mod pagetable;
+mod shootdown;
 pub mod kerneltable;
 
 pub use pageentry::{
@@ -23,10 +24,16 @@ pub use pagetable::{
 pub use kerneltable::{
 	kernel_offset
 };
+pub use shootdown::{
+	connect_tlb_shootdown,
+	shootdown_tlb,
+	flush_requested_tlb
+};
 
 use crate::std::{
 	PerCpu,
-	Mutex
+	Mutex,
+	KernelError
 };
 
 #[derive(Debug)]
@@ -38,6 +45,16 @@ pub enum PagingError {
 	DirectoryAssociationFailed
 }
 
+impl From<PagingError> for KernelError {
+	fn from(error: PagingError) -> KernelError {
+		match error {
+			PagingError::AttemptedNonPresentUnmap | PagingError::OutOfBoundsIndex => KernelError::BadAddress,
+			PagingError::DirectoryAssociationFailed => KernelError::OutOfMemory,
+			PagingError::GeneralError | PagingError::SnappingError => KernelError::InvalidArgument
+		}
+	}
+}
+
 pub const PAGE_SIZES: [usize; 5] = [
 	0x0,
 	0x1000,
@@ -49,6 +66,13 @@ pub const PAGE_SIZES: [usize; 5] = [
 static INITIAL_PAGE_TABLE: Mutex<PageTable> = Mutex::new(PageTable::EMPTY);
 static GLOBAL_PAGE_TABLE_MUTEX: PerCpu<&Mutex<PageTable>> = PerCpu::new(&INITIAL_PAGE_TABLE);
 
+/*
+ * The page table set up while booting. Only the kernel is mapped
+ * in it, so it´s used, when no process runs on a core.
+ */
+pub fn initial_page_table() -> &'static Mutex<PageTable> {
+	&INITIAL_PAGE_TABLE
+}
 pub fn current_page_table() -> &'static Mutex<PageTable> {
 	GLOBAL_PAGE_TABLE_MUTEX.deref()
 }
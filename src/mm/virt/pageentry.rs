@@ -41,6 +41,13 @@ impl PageTableEntry {
 	pub fn set_flags(&mut self, flags: u64) {
 		self.content |= flags;
 	}
+	pub fn set_writable(&mut self, writable: bool) {
+		if writable {
+			self.content |= 0x2;
+		} else {
+			self.content &= !0x2;
+		}
+	}
 	pub fn addr(&self) -> u64 {
 		self.content & !0xfff
 	}
@@ -8,13 +8,17 @@ use super::{
 use crate::std::{
 	Mutex,
 	Box,
+	Vec,
 	log
 };
 use core::{
 	ops::Index,
 	iter::*
 };
-use crate::mm::align_size;
+use crate::mm::{
+	align_size,
+	buddy
+};
 use crate::assume_safe_asm;
 
 pub struct PageTable {
@@ -24,7 +28,12 @@ pub struct PageTable {
 	temporary_index: u64,
 	physical_offset: u64,
 	initalized: bool,
-	cr3: u64
+	cr3: u64,
+	/*
+	 * Physical addresses of the directories allocated for this table.
+	 * Shared directories, like the kernel and global ones, aren´t included.
+	 */
+	allocated_directories: Vec<u64>
 }
 
 const TEMPORARY_ADDRESS_SPACE: u64 = 0x8000000000;
@@ -42,7 +51,8 @@ impl PageTable {
 		temporary_index: 0,
 		physical_offset: 0,
 		initalized: false,
-		cr3: 0
+		cr3: 0,
+		allocated_directories: Vec::new()
 	};
 	const LEVELS: u64 = 4;
 
@@ -109,13 +119,37 @@ impl PageTable {
 		for level in (size_as_page_level(size)+1..Self::LEVELS).rev() {
 			let idx = virt_addr / page_level_as_size(level) as u64;
 			virt_addr %= page_level_as_size(level) as u64;
-			directory = directory[idx as usize].mut_dir()?;
+			let entry = &mut directory[idx as usize];
+			if !entry.is_present() {
+				return None;
+			}
+			directory = entry.mut_dir()?;
+		}
+		Some(&mut directory[virt_addr as usize / size])
+	}
+	/*
+	 * Like get_page_entry_mut, but missing directories are allocated.
+	 * Only mapping uses it, the other operations fail on unmapped ranges.
+	 */
+	fn create_page_entry(&mut self, mut virt_addr: u64, size: usize) -> Option<&mut PageTableEntry> {
+		let mut directory: &mut PageDirectory = &mut self.directory;
+
+		for level in (size_as_page_level(size)+1..Self::LEVELS).rev() {
+			let idx = virt_addr / page_level_as_size(level) as u64;
+			virt_addr %= page_level_as_size(level) as u64;
+			let entry = &mut directory[idx as usize];
+			if !entry.is_present() {
+				let addr = buddy::allocate_aligned(0x1000)?;
+				entry.set_dir_addr(addr);
+				self.allocated_directories.push_back(addr);
+			}
+			directory = entry.mut_dir()?;
 		}
 		Some(&mut directory[virt_addr as usize / size])
 	}
 	fn map_page(&mut self, virt_addr: u64, phys_addr: u64, size: usize, flags: u64) -> bool {
 		assert!(phys_addr & 0xfff == 0, "Attempt to map unaligned address: {:x}", phys_addr);
-		if let Some(entry) = self.get_page_entry_mut(virt_addr, size) {
+		if let Some(entry) = self.create_page_entry(virt_addr, size) {
 			entry.set_addr(phys_addr, size);
 			entry.set_flags(flags);
 			true
@@ -209,6 +243,96 @@ impl PageTable {
 		PageTable::flush();
 		true
 	}
+	/*
+	 * Allows or forbids writing to mapped 4 KiB pages. Used to share
+	 * pages copy on write. Only the entries of the pages change, never
+	 * the directories, so is_writable sees the same permission.
+	 * Other cores running with the table need a TLB shootdown.
+	 */
+	pub fn set_writable(&mut self, virt_addr: u64, amount: usize, writable: bool) -> bool {
+		for offset in (0..amount as u64).step_by(0x1000) {
+			if let Some(entry) = self.get_page_entry_mut(virt_addr + offset, 0x1000) {
+				entry.set_writable(writable);
+			} else {
+				return false;
+			}
+		}
+		PageTable::flush();
+		true
+	}
+	// Writing is allowed, if every level of the page has the R/W flag.
+	pub fn is_writable(&self, virt_addr: u64) -> bool {
+		self.mapped_page_size(virt_addr, 0x2).is_some()
+	}
+	/*
+	 * Returns the size of the page containing the address, if it´s
+	 * present and has the flags in every level, e.g. the user flag.
+	 * Unlike get_page_entry, huge pages are supported.
+	 */
+	pub fn mapped_page_size(&self, virt_addr: u64, flags: u64) -> Option<usize> {
+		let mut remainder = virt_addr & 0xffffffffffff;
+		let mut directory: &PageDirectory = &self.directory;
+
+		for level in (0..Self::LEVELS).rev() {
+			let entry = &directory[(remainder / page_level_as_size(level) as u64) as usize];
+			remainder %= page_level_as_size(level) as u64;
+			if !entry.is_present() || entry.content & flags != flags {
+				return None;
+			}
+			if !entry.is_dir() || level == 0x0 {
+				return Some(page_level_as_size(level));
+			}
+			directory = entry.dir()?;
+		}
+		None
+	}
+	/*
+	 * Whether nothing is mapped in the range. Missing directories are
+	 * skipped at once, so even huge ranges are checked quickly.
+	 */
+	pub fn is_unmapped(&self, virt_addr: u64, size: usize) -> bool {
+		let end = virt_addr.saturating_add(size as u64);
+		let mut address = virt_addr;
+		while address < end {
+			let Some(unmapped_size) = self.unmapped_size(address) else {
+				return false;
+			};
+			address = (address & !(unmapped_size as u64 - 1)).saturating_add(unmapped_size as u64);
+		}
+		true
+	}
+	/*
+	 * Returns the size of the missing entry or directory at the
+	 * address or None, if a page is mapped there.
+	 */
+	fn unmapped_size(&self, virt_addr: u64) -> Option<usize> {
+		let mut remainder = virt_addr & 0xffffffffffff;
+		let mut directory: &PageDirectory = &self.directory;
+
+		for level in (0..Self::LEVELS).rev() {
+			let entry = &directory[(remainder / page_level_as_size(level) as u64) as usize];
+			remainder %= page_level_as_size(level) as u64;
+			if !entry.is_present() {
+				return Some(page_level_as_size(level));
+			}
+			if !entry.is_dir() || level == 0x0 {
+				return None;
+			}
+			directory = entry.dir()?;
+		}
+		None
+	}
+	/*
+	 * Frees all directories allocated for this table. The table
+	 * mustn´t be used afterwards.
+	 */
+	pub fn free_directories(&mut self) {
+		for addr in &self.allocated_directories {
+			buddy::free(*addr, 0x1000);
+		}
+		self.allocated_directories.clear();
+		self.initalized = false;
+	}
 	pub fn mapped_temporary<T>(&self, phys_addr: u64, size: usize) -> &'static mut T {
 		if size != 0x1000 {
 			todo!("Temporary mapping support for other sizes than 4k.");
@@ -0,0 +1,78 @@
+/*
+ * Cores cache the translations of the page table they run with. After
+ * a page lost its write access or was unmapped, every other core running
+ * with the table flushes its TLB, before the page is shared or freed.
+ */
+
+use core::sync::atomic::{
+	AtomicU64,
+	Ordering
+};
+use crate::std::{
+	Mutex,
+	PerCpu,
+	MAX_CORES,
+	current_core
+};
+use crate::hw::cpu::{
+	self,
+	LAPIC,
+	TLB_SHOOTDOWN
+};
+use super::{
+	PageTable,
+	GLOBAL_PAGE_TABLE_MUTEX
+};
+
+// Incremented for every shootdown, which targets the core.
+static REQUESTED: PerCpu<AtomicU64> = PerCpu::from_array(
+	[const { AtomicU64::new(0) }; MAX_CORES]
+);
+// Last request, which the core handled.
+static FLUSHED: PerCpu<AtomicU64> = PerCpu::from_array(
+	[const { AtomicU64::new(0) }; MAX_CORES]
+);
+
+/*
+ * Connects the shootdown vector of the current core.
+ */
+pub fn connect_tlb_shootdown() {
+	cpu::connect_signal(TLB_SHOOTDOWN, |_| flush_requested_tlb());
+}
+
+/*
+ * Flushes the TLB of the current core and of every other core, which
+ * runs with the page table. Returns, after all of them flushed.
+ * The page table has to be changed already.
+ */
+pub fn shootdown_tlb(page_table: &Mutex<PageTable>) {
+	PageTable::flush();
+	let current = current_core() as usize;
+	let mut requests = [0x0; MAX_CORES];
+	for (core, request) in requests.iter_mut().enumerate() {
+		if core != current && core::ptr::eq(*GLOBAL_PAGE_TABLE_MUTEX.get(core), page_table) {
+			*request = REQUESTED.get(core).fetch_add(1, Ordering::SeqCst) + 1;
+			LAPIC::send_interrupt(core, TLB_SHOOTDOWN);
+		}
+	}
+	for (core, request) in requests.into_iter().enumerate() {
+		while FLUSHED.get(core).load(Ordering::SeqCst) < request {
+			// Another core may wait for a flush of this one at the same time.
+			flush_requested_tlb();
+			core::hint::spin_loop();
+		}
+	}
+}
+
+/*
+ * Flushes the TLB of the current core, if another core requested it.
+ * Called by the shootdown vector and by cores waiting actively with
+ * disabled interrupts, which may wait for a lock held by the requester.
+ */
+pub fn flush_requested_tlb() {
+	let requested = REQUESTED.deref().load(Ordering::SeqCst);
+	if FLUSHED.deref().load(Ordering::SeqCst) < requested {
+		PageTable::flush();
+		FLUSHED.deref().fetch_max(requested, Ordering::SeqCst);
+	}
+}
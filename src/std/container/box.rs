@@ -23,6 +23,7 @@ use crate::std::{
 	alloc::VirtualMapper
 };
 
+#[derive(Clone, Copy)]
 enum InternFlags {
 	None,
 	UnmapOnly
@@ -62,6 +63,20 @@ impl<T: ?Sized, A: Allocator + Default> Box<T, A> {
 			InternFlags::None
 		)
 	}
+	/*
+	 * Like new_sized, but returns None instead of
+	 * panicking, when there´s not enough memory.
+	 */
+	pub fn try_new_sized(size: usize) -> Option<Box<T, A>> {
+		Some(Box(
+			NonNull::new(
+				A::default().allocate(size)?
+			)?,
+			A::default(),
+			size,
+			InternFlags::None
+		))
+	}
 	pub fn from_raw_address_sized(phys_addr: u64, size: usize) -> Box<T, A> {
 		let addr = A::VirtualMapper::default().map::<u8>(stack_vec!{ phys_addr }, size).unwrap() as u64;
 		Self::from_raw_virt_address_sized(
@@ -89,6 +104,18 @@ impl<T: ?Sized, A: Allocator + Default> Box<T, A> {
 	pub fn null() -> Box<T, A> {
 		Self::from_raw_virt_address_sized(0x1, 0x0)
 	}
+	/*
+	 * Returns another box of the same allocation.
+	 * Only one of them may be freed.
+	 */
+	pub unsafe fn alias(&self) -> Box<T, A> {
+		Box(
+			self.0,
+			A::default(),
+			self.2,
+			self.3
+		)
+	}
 }
 
 impl<T, A: Allocator> Box<T, A> {
@@ -125,6 +152,15 @@ impl<T: ?Sized, A: Allocator> Box<T, A> {
 	pub fn as_stack(&self) -> *mut u8 {
 		(self.0.as_ptr() as *mut u8).wrapping_byte_add(self.alloc_len())
 	}
+	/*
+	 * Frees the allocation. Dropping a box doesn´t free it,
+	 * so memory, that isn´t used anymore, has to be freed explicitly.
+	 */
+	pub fn free(self) {
+		if let InternFlags::None = self.3 {
+			self.1.free(self.0.as_ptr() as *const u8, self.alloc_len());
+		}
+	}
 }
 
 impl<T: Copy, A: Allocator + Default> Box<[T], A> {
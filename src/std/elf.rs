@@ -1,65 +1,192 @@
 use crate::kernel::{
+	current_process,
 	ProcessPrivilage,
-	Process
+	ProcessFlags,
+	Process,
+	FileDescriptors
 };
 use crate::std::{
 	self,
-	BasicAllocator,
-	PhysicalRAMAllocator,
-	PageTableMapper,
-	Allocator
-};
-use crate::mm::{
-	MappingInfo,
-	MappingFlags
+	Box,
+	String,
+	Vec,
+	VecBase,
+	KernelError,
+	KernelResult
 };
 use elf_rs::{
 	ElfFile,
 	ProgramType
 };
+use abi::process::{
+	AT_NULL,
+	AT_PHDR,
+	AT_PHENT,
+	AT_PHNUM,
+	AT_PAGESZ,
+	AT_ENTRY
+};
+
+const USER_STACK_ADDRESS: u64 = 0x7f0000000000;
+const USER_STACK_SIZE: usize = 0x10000;
+// Arguments may use this much of the stack, the rest is left for the program.
+const MAX_ARGUMENT_SPACE: usize = USER_STACK_SIZE / 2;
+const PAGE_SIZE: u64 = 0x1000;
+
+/*
+ * Arguments and environment variables, which are
+ * passed on the stack of a new program.
+ */
+pub struct ProgramArguments {
+	pub arguments: Vec<String>,
+	pub environment: Vec<String>
+}
+
+impl ProgramArguments {
+	pub fn new() -> ProgramArguments {
+		ProgramArguments {
+			arguments: Vec::new(),
+			environment: Vec::new()
+		}
+	}
+}
+
+/*
+ * Starts a process running the ELF at the absolute path.
+ */
+pub fn load_elf_from_file(path: &String, arguments: &ProgramArguments, files: &FileDescriptors) -> KernelResult<u64> {
+	let info = std::stat_file(path)?;
+	if info.is_directory {
+		return Err(KernelError::IsDirectory);
+	}
+	let data = std::read_file(path, info.size, 0)?;
+	let result = load_elf(data.as_slice(), arguments, files);
+	data.free();
+	result
+}
+
+/*
+ * Starts a process running the ELF. It refers to the same open
+ * files as the descriptors and starts in the working directory
+ * of the current process. Returns the pid of the process.
+ */
+pub fn load_elf(data: &[u8], arguments: &ProgramArguments, files: &FileDescriptors) -> KernelResult<u64> {
+	let elffile = elf_rs::Elf::from_bytes(data).map_err(|_| KernelError::InvalidExecutable)?;
+	let mut process = Process::new(ProcessPrivilage::USER, elffile.entry_point()).ok_or(KernelError::OutOfMemory)?;
+	// The process isn´t spawned yet, so it´s freed with its page table and mappings.
+	if let Err(error) = map_program(&mut process, &elffile, data, arguments) {
+		process.free();
+		return Err(error);
+	}
+	process.assign_files(files.share());
+	match current_process() {
+		Some(parent) if !parent.lock().is_kernel_task() => {
+			process.working_directory = parent.lock().working_directory.clone();
+		},
+		// Programs started by the kernel are privileged.
+		_ => process.flags |= ProcessFlags::Privileged as u64
+	}
+	let pid = process.pid;
+	process.spawn();
+	Ok(pid)
+}
+
+/*
+ * Maps the loadable segments and the stack with the arguments of the
+ * program. Memory of segments beyond the file content is zeroed.
+ */
+fn map_program(process: &mut Process, elffile: &elf_rs::Elf, data: &[u8], arguments: &ProgramArguments) -> KernelResult<()> {
+	let header = elffile.elf_header();
+	let mut program_headers = 0x0;
 
-pub fn load_elf_from_file(fs_id: usize, file_path: std::FilePath) -> bool {
-	if let Ok(data) = std::read_file(fs_id, file_path, usize::MAX, 0) {
-		load_elf(data.as_slice())
-	} else {
-		false
+	for entry in elffile.program_header_iter() {
+		if entry.ph_type() != ProgramType::LOAD {
+			continue;
+		}
+		if entry.memsz() < entry.filesz() || entry.offset().checked_add(entry.filesz()).is_none_or(|end| end > data.len() as u64) {
+			return Err(KernelError::InvalidExecutable);
+		}
+		// The program headers are only visible to the program, if a segment contains them.
+		// Otherwise AT_PHDR is zero.
+		if (entry.offset()..entry.offset() + entry.filesz()).contains(&header.program_header_offset()) {
+			program_headers = entry.vaddr() + header.program_header_offset() - entry.offset();
+		}
+		let mut flags = 0x4;
+		if !entry.flags().contains(elf_rs::ProgramHeaderFlags::EXECUTE) {
+			flags |= 0x8000000000000000;
+		}
+
+		process.add_unaligned_mapping(
+			entry.vaddr(),
+			data,
+			entry.offset() as usize,
+			entry.filesz() as usize,
+			entry.memsz() as usize,
+			flags
+		)?;
 	}
+
+	let auxiliary = [
+		(AT_PHDR, program_headers),
+		(AT_PHENT, header.program_header_entry_size() as u64),
+		(AT_PHNUM, header.program_header_entry_num() as u64),
+		(AT_PAGESZ, PAGE_SIZE),
+		(AT_ENTRY, elffile.entry_point()),
+		(AT_NULL, 0x0)
+	];
+	// The stack is a mapping of the process, so it´s freed with it.
+	let mut stack: Box<[u8]> = Box::new_sized(USER_STACK_SIZE);
+	let stack_pointer = match build_initial_stack(stack.as_slice_mut(), arguments, &auxiliary) {
+		Ok(stack_pointer) => stack_pointer,
+		Err(error) => {
+			stack.free();
+			return Err(error);
+		}
+	};
+	process.add_mapping(USER_STACK_ADDRESS, stack, 0x8000000000000004);
+	process.assign_stack(USER_STACK_ADDRESS + stack_pointer as u64);
+	Ok(())
 }
 
-pub fn load_elf(data: &[u8]) -> bool {
-	if let Ok(elffile) = elf_rs::Elf::from_bytes(data) {
-		let mut process = Process::new(ProcessPrivilage::USER, elffile.entry_point()).unwrap();
+/*
+ * Lays out the arguments, the environment and the auxiliary vector at
+ * the top of the stack like the System V ABI does. The strings are at
+ * the top, argc is at the returned offset of the stack pointer, which
+ * is aligned to 16 bytes.
+ */
+fn build_initial_stack(stack: &mut [u8], arguments: &ProgramArguments, auxiliary: &[(u64, u64)]) -> KernelResult<usize> {
+	let lists = [&arguments.arguments, &arguments.environment];
+	let strings_size: usize = lists.iter()
+		.flat_map(|list| list.into_iter())
+		.map(|string| string.bytes().len() + 1)
+		.sum();
+	let words = 1 + arguments.arguments.len() + 1 + arguments.environment.len() + 1 + auxiliary.len() * 2;
+	if strings_size + words * 8 + 0x10 > MAX_ARGUMENT_SPACE {
+		return Err(KernelError::ArgumentListTooLong);
+	}
 
-		for entry in elffile.program_header_iter() {
-			if entry.ph_type() != ProgramType::LOAD {
-				continue;
-			}
-			let mut flags = 0x4;
-			if !entry.flags().contains(elf_rs::ProgramHeaderFlags::EXECUTE) {
-				flags |= 0x8000000000000000;
-			}
+	let mut string_offset = stack.len() - strings_size;
+	let stack_pointer = (string_offset - words * 8) & !0xf;
+	let mut word_offset = stack_pointer;
+	let mut push_word = |stack: &mut [u8], value: u64| {
+		stack[word_offset..word_offset + 8].copy_from_slice(&value.to_le_bytes());
+		word_offset += 8;
+	};
 
-			process.add_unaligned_mapping(
-				entry.vaddr(),
-				data,
-				entry.offset() as usize,
-				entry.filesz() as usize,
-				flags
-			);
+	push_word(stack, arguments.arguments.len() as u64);
+	for list in lists {
+		for string in list {
+			let bytes = string.bytes();
+			push_word(stack, USER_STACK_ADDRESS + string_offset as u64);
+			stack[string_offset..string_offset + bytes.len()].copy_from_slice(bytes);
+			stack[string_offset + bytes.len()] = 0x0;
+			string_offset += bytes.len() + 1;
 		}
-		process.assign_stack(BasicAllocator::<PageTableMapper, PhysicalRAMAllocator>::new(
-			PageTableMapper(
-				MappingInfo {
-					page_table: &process.page_table,
-					addresses: &[],
-					address_amount: 0,
-					flags: MappingFlags::User
-				}
-			)
-		).allocate::<u8>(0x1000).unwrap() as u64 + 0x1000);
-		process.spawn();
-		true
-	} else {
-		false
+		push_word(stack, 0x0);
+	}
+	for (r#type, value) in auxiliary {
+		push_word(stack, *r#type);
+		push_word(stack, *value);
 	}
+	Ok(stack_pointer)
 }
@@ -0,0 +1,8 @@
+/*
+ * Kernel-wide error. It´s the error enum of the abi crate, so
+ * errors keep their code, when a syscall returns them. Errors
+ * of subsystems like FSError convert to it with From.
+ */
+pub use abi::error::Error as KernelError;
+
+pub type KernelResult<T> = Result<T, KernelError>;
@@ -1,15 +1,34 @@
 pub use crate::virt::fs::{
 	self,
-	FilePath,
+	FileInfo,
 	FSError,
 	MountPoint
 };
-use crate::std::Box;
+use crate::std::{
+	Box,
+	String,
+	VecBase
+};
+
+/*
+ * Paths of these functions are absolute paths of the namespace.
+ */
+pub fn read_file(path: &String, amount: usize, offset: usize) -> Result<Box<[u8]>, FSError> {
+	let (filesystem, path) = fs::resolve(path)?;
+	fs::filesystem(filesystem)?.read(path, offset, amount)
+}
 
-pub fn read_file(fs_id: usize, path: FilePath, amount: usize, offset: usize) -> Result<Box<[u8]>, FSError> {
-	fs::filesystem(fs_id).read(path, amount, offset)
+pub fn stat_file(path: &String) -> Result<FileInfo, FSError> {
+	let (filesystem, path) = fs::resolve(path)?;
+	fs::filesystem(filesystem)?.stat(path)
 }
 
-pub fn mount(disk_id: usize) -> usize {
-	fs::mount(MountPoint::from_disk(disk_id))
+/*
+ * Mounts the filesystem of the disk at the directory of the path.
+ */
+pub fn mount(disk_id: usize, path: &String) -> Result<(), FSError> {
+	if disk_id >= crate::hw::disk_ids().len() {
+		return Err(FSError::UnknownFileSystem);
+	}
+	fs::mount(MountPoint::from_disk(disk_id), path)
 }
@@ -6,6 +6,14 @@ pub fn outb(val: u8, port: u16) {
 	}
 }
 
+pub fn inb(port: u16) -> u8 {
+	let val: u8;
+	unsafe {
+		asm!("inb %dx, %al", out("al") val, in("dx") port, options(att_syntax));
+	}
+	val
+}
+
 pub fn outw(val: u16, port: u16) {
 	unsafe {
 		asm!("outw %ax, %dx", in("ax") val, in("dx") port, options(att_syntax));
@@ -9,6 +9,7 @@ mod log_intern;
 mod console;
 mod file;
 mod random;
+mod error;
 
 pub mod elf;
 pub mod log {
@@ -40,10 +41,12 @@ pub use thread::{
 	mutex::OptMutexGuard,
 	mutex::MutexGuard,
 	lock::Lock,
+	waitqueue::WaitQueue,
 	lazymutex::LazyMutex,
 	lazymutex::LazyMutexGuard,
 	percpu::PerCpuLazy,
 	percpu::PerCpu,
+	percpu::MAX_CORES,
 	count_cores,
 	current_core,
 	current_core_uncached
@@ -51,33 +54,40 @@ pub use thread::{
 pub use file::{
 	mount,
 	read_file,
-	FilePath
+	stat_file
 };
 pub use alloc::{
 	Allocator,
 	RAMAllocator,
 	BasicAllocator,
-	PhysicalRAMAllocator,
 	PageTableMapper,
 	CustomRAMAllocator
 };
 pub use utils::{
 	hltloop,
+	hlt,
 	cli,
 	sti,
+	interrupts_enabled,
 	cr2,
 	reset_cr2,
 	wrmsr,
 	rdmsr,
+	rdtsc,
 	wait
 };
+pub use error::{
+	KernelError,
+	KernelResult
+};
 pub use reversebytes::ReverseBytes;
 pub use io::{
-	outb
+	outb,
+	inb
 };
 pub use crate::kernel::r#yield;
 pub use random::random;
 
 pub fn exit() -> ! {
-	crate::kernel::exit_current_process()
+	crate::kernel::exit_current_process(0)
 }
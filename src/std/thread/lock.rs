@@ -2,32 +2,41 @@ use core::sync::atomic::{
 	AtomicBool,
 	Ordering
 };
+use super::waitqueue::WaitQueue;
 
+/*
+ * Processes waiting for the lock are blocked,
+ * until it´s unlocked.
+ */
 pub struct Lock {
-	state: AtomicBool
+	state: AtomicBool,
+	waiters: WaitQueue
 }
 
 impl Lock {
 	pub const fn new() -> Lock {
 		Lock {
-			state: AtomicBool::new(false)
+			state: AtomicBool::new(false),
+			waiters: WaitQueue::new()
 		}
 	}
 	pub const fn new_locked() -> Lock {
 		Lock {
-			state: AtomicBool::new(true)
+			state: AtomicBool::new(true),
+			waiters: WaitQueue::new()
 		}
 	}
 
 	pub fn lock(&self) {
 		while self.state.swap(true, Ordering::Acquire) {
-			crate::std::wait();
+			self.waiters.wait_until(|| !self.is_locked());
 		}
 		self.state.store(true, Ordering::Release);
 	}
 
 	pub fn unlock(&self) {
-		self.state.store(false, Ordering::Release);
+		self.state.store(false, Ordering::SeqCst);
+		self.waiters.wake_all();
 	}
 
 	pub fn is_locked(&self) -> bool {
@@ -35,9 +44,7 @@ impl Lock {
 	}
 
 	pub fn wait(&self) {
-		while self.is_locked() {
-			crate::std::wait();
-		}
+		self.waiters.wait_until(|| !self.is_locked());
 	}
 }
 
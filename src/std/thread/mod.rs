@@ -2,6 +2,7 @@ pub mod lock;
 pub mod mutex;
 pub mod percpu;
 pub mod lazymutex;
+pub mod waitqueue;
 
 use core::arch::x86_64::__cpuid;
 
@@ -7,8 +7,11 @@ use crate::std::{
 	LazyBox
 };
 
+// Cores are indexed by their LAPIC id.
+pub const MAX_CORES: usize = 32;
+
 pub struct PerCpu<T> {
-	content: UnsafeCell<[T; 32]>
+	content: UnsafeCell<[T; MAX_CORES]>
 }
 
 pub struct PerCpuLazy<T>(PerCpu<LazyBox<T>>);
@@ -16,13 +19,18 @@ pub struct PerCpuLazy<T>(PerCpu<LazyBox<T>>);
 impl<T: Copy> PerCpu<T> {
 	pub const fn new(value: T) -> PerCpu<T> {
 		PerCpu {
-			content: UnsafeCell::new([value; 32])
+			content: UnsafeCell::new([value; MAX_CORES])
 		}
 	}
 }
 
 impl<T> PerCpu<T> {
-	pub fn unwrap(&self) -> &mut [T; 32] {
+	pub const fn from_array(content: [T; MAX_CORES]) -> PerCpu<T> {
+		PerCpu {
+			content: UnsafeCell::new(content)
+		}
+	}
+	pub fn unwrap(&self) -> &mut [T; MAX_CORES] {
 		unsafe {
 			self.content.get().as_mut().unwrap()
 		}
@@ -30,6 +38,10 @@ impl<T> PerCpu<T> {
 	pub fn deref(&self) -> &T {
 		self.unwrap().each_ref()[current_core() as usize]
 	}
+	// Content of another core.
+	pub fn get(&self, core: usize) -> &T {
+		&self.unwrap()[core]
+	}
 	pub fn deref_mut(&self) -> &mut T {
 		self.unwrap().each_mut()[current_core() as usize]
 	}
@@ -0,0 +1,67 @@
+use core::sync::atomic::{
+	AtomicUsize,
+	Ordering
+};
+use crate::kernel::scheduler;
+use crate::std::interrupts_enabled;
+
+/*
+ * Parks processes, until a condition they´re waiting for changes.
+ * The queue only counts its waiters, the parked processes
+ * remember the queue they wait on themselves.
+ */
+pub struct WaitQueue {
+	waiters: AtomicUsize
+}
+
+impl WaitQueue {
+	pub const fn new() -> WaitQueue {
+		WaitQueue {
+			waiters: AtomicUsize::new(0)
+		}
+	}
+
+	/*
+	 * Blocks the current process, until condition returns true.
+	 * The condition is checked again after every wake up.
+	 * Waits actively, when no process can be blocked,
+	 * e.g. with disabled interrupts or while booting.
+	 */
+	pub fn wait_until<F: Fn() -> bool>(&self, condition: F) {
+		self.park(&condition, false);
+	}
+
+	/*
+	 * Like wait_until, but blocks with disabled interrupts too.
+	 * Syscalls run with disabled interrupts, but they don´t
+	 * hold any lock, when they wait.
+	 */
+	pub fn block_until<F: Fn() -> bool>(&self, condition: F) {
+		self.park(&condition, true);
+	}
+
+	/*
+	 * Wakes every process waiting on the queue.
+	 * Must be called after the condition changed.
+	 */
+	pub fn wake_all(&self) {
+		if self.waiters.load(Ordering::SeqCst) > 0 {
+			scheduler::wake_channel(self.channel());
+		}
+	}
+
+	fn park(&self, condition: &dyn Fn() -> bool, always_block: bool) {
+		while !condition() {
+			self.waiters.fetch_add(1, Ordering::SeqCst);
+			let blocked = (always_block || interrupts_enabled()) && scheduler::block_current(self.channel(), condition);
+			self.waiters.fetch_sub(1, Ordering::SeqCst);
+			if !blocked {
+				crate::std::wait();
+			}
+		}
+	}
+
+	fn channel(&self) -> u64 {
+		self as *const WaitQueue as u64
+	}
+}
@@ -22,8 +22,15 @@ pub fn hltloop() -> ! {
 	}
 }
 
+#[inline]
+pub fn hlt() {
+	assume_safe_asm!("hlt");
+}
+
 #[inline]
 pub fn wait() {
+	// Cores waiting with disabled interrupts miss TLB shootdowns otherwise.
+	crate::mm::flush_requested_tlb();
 	if is_initalized() {
 		super::r#yield();
 	} else {
@@ -42,6 +49,17 @@ pub fn sti() {
 	assume_safe_asm!("sti");
 }
 
+#[inline]
+pub fn interrupts_enabled() -> bool {
+	let rflags: u64;
+	unsafe {
+		asm!("pushfq",
+			 "pop {0}",
+			 out(reg) rflags);
+	}
+	rflags & 0x200 != 0
+}
+
 #[inline]
 pub fn cr2() -> u64 {
 	let cr2;
@@ -60,6 +78,18 @@ pub fn reset_cr2() {
 	}
 }
 
+/*
+ * Reads the time stamp counter of the current core.
+ */
+#[inline]
+pub fn rdtsc() -> u64 {
+	let (high, low): (u32, u32);
+	unsafe {
+		asm!("rdtsc", out("eax") low, out("edx") high);
+	}
+	((high as u64) << 32) | (low as u64)
+}
+
 #[inline]
 pub fn wrmsr(msr: u32, value: u64) {
 	unsafe {
@@ -96,6 +96,26 @@ impl<T, A: Allocator> Vec<T, A> {
 	pub fn empty(&self) -> bool {
 		self.len() == 0
 	}
+	/*
+	 * Removes the element at index and shifts every following
+	 * element one index down.
+	 */
+	pub fn remove(&mut self, index: usize) -> T {
+		let removed = unsafe {
+			core::ptr::read(self.index_ptr_mut(index))
+		};
+		for idx in index..self.length - 1 {
+			unsafe {
+				core::ptr::copy_nonoverlapping(
+					self.index_ptr_mut(idx + 1),
+					self.index_ptr_mut(idx),
+					1
+				);
+			}
+		}
+		self.length -= 1;
+		removed
+	}
 
 	// FIXME: Fix large memory consumption after clearing.
 	pub fn clear(&mut self) {
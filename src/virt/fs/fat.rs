@@ -1,16 +1,27 @@
 use crate::{
 	hw::Sector,
+	hw::SECTOR_SIZE,
 	hw::read_lba,
 	hw::read_lbas,
+	hw::write_lba,
+	hw::write_lbas,
 
+	hw::clock::realtime_ns,
+	hw::clock::days_since_epoch,
+	hw::clock::date_from_days,
+	hw::clock::NS_PER_SECOND
 };
 use crate::std::{
 	Box,
 	String,
-	VecBase
+	Vec,
+	VecBase,
+	Mutex
 };
 use super::{
 	FileStructure,
+	FileInfo,
+	DirectoryItem,
 	MountPoint,
 	FilePath,
 	FSError
@@ -21,12 +32,31 @@ pub struct FAT32 {
 
 	boot_sector: Sector,
 	boot_sector_info: Box<BootSector>,
-	root_directory_sector: Sector,
-	root_directory: Directory,
 
 	// Precalculated values
+	fat_lba: usize,
 	data_lba: usize,
-	cluster_size: usize // In bytes
+	cluster_size: usize, // In bytes
+	cluster_amount: usize, // Clusters in the data region
+
+	// None without a valid FSInfo sector.
+	fs_info_lba: Option<usize>,
+	// Hints of the FSInfo sector, they´re kept up to date while clusters are allocated and freed.
+	free_clusters: Option<usize>,
+	next_free_cluster: usize,
+
+	// Where the latest reads of chains ended, so reading files in pieces doesn´t follow the chain from its start.
+	read_positions: Mutex<Vec<ChainPosition>>
+}
+
+/*
+ * The cluster at the index of the chain, which starts with the first cluster.
+ */
+#[derive(Clone, Copy)]
+struct ChainPosition {
+	first_cluster: usize,
+	index: usize,
+	cluster: usize
 }
 
 #[repr(C, packed)]
@@ -53,8 +83,6 @@ pub struct BootSector {
 	bakup_boot_sector: u16
 }
 
-pub type Directory = Box<[DirectoryEntry]>;
-
 #[repr(C, packed)]
 #[derive(Clone, Copy)]
 pub struct DirectoryEntry {
@@ -72,17 +100,806 @@ pub struct DirectoryEntry {
 	file_size: u32
 }
 
+/*
+ * Part of a long file name. They precede the entry of the file
+ * in reverse order, the last part comes first.
+ */
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+struct LongNameEntry {
+	order: u8,
+	name1: [u16; 5],
+	attributes: u8,
+	kind: u8,
+	checksum: u8,
+	name2: [u16; 6],
+	first_data_cluster_low: u16,
+	name3: [u16; 2]
+}
+
+/*
+ * Entry of a directory together with its name.
+ */
+struct NamedEntry {
+	name: String,
+	entry: DirectoryEntry,
+	// Index of the entry in the directory. The parts of its long file name precede it.
+	slot: usize,
+	long_name_parts: usize
+}
+
+/*
+ * Entry found by its path. The root directory isn´t
+ * contained in a directory.
+ */
+struct FoundEntry {
+	directory: Option<usize>,
+	named_entry: NamedEntry
+}
+
+/*
+ * Reads entries of the File Allocation Table. Consecutive
+ * clusters mostly share a sector, so the sector read last is kept.
+ */
+struct FatReader {
+	sector: Option<(usize, Sector)>
+}
+
+/*
+ * Date and time in the format of directory entries.
+ */
+struct Timestamp {
+	date: u16,
+	time: u16,
+	tenths: u8
+}
+
+/*
+ * Long file name, while its parts are collected.
+ */
+struct LongName {
+	units: [u16; MAX_LONG_NAME_PARTS * LONG_NAME_PART_LENGTH],
+	checksum: u8,
+	parts: u8,
+	// Order of the part expected next, zero once every part was read.
+	next_order: u8
+}
+
+// Attributes of directory entries
+const ATTRIBUTE_VOLUME_ID: u8 = 0x08;
+const ATTRIBUTE_DIRECTORY: u8 = 0x10;
+const ATTRIBUTE_ARCHIVE: u8 = 0x20;
+// Long file name entries have all of these attributes.
+const ATTRIBUTE_LONG_NAME: u8 = 0x0f;
+// First name bytes with a special meaning
+const END_OF_DIRECTORY: u8 = 0x00;
+const DELETED_ENTRY: u8 = 0xe5;
+// Names starting with 0xe5 are stored starting with 0x05 instead.
+const ESCAPED_DELETED_MARKER: u8 = 0x05;
+// Marks the part of a long file name, which is stored first.
+const LAST_LONG_NAME_PART: u8 = 0x40;
+const LONG_NAME_ORDER_MASK: u8 = 0x1f;
+// Long file names have up to 255 characters in 20 parts of 13 each.
+const MAX_LONG_NAME_PARTS: usize = 20;
+const LONG_NAME_PART_LENGTH: usize = 13;
+const MAX_LONG_NAME_LENGTH: usize = 255;
+// Characters, which names can´t contain.
+const INVALID_NAME_CHARACTERS: &str = "\"*/:<>?\\|";
+// Characters of 8.3 names besides uppercase letters and digits.
+const SHORT_NAME_SPECIAL_CHARACTERS: &[u8] = b"!#$%&'()-@^_`{}~";
+const DOT_NAME: [u8; 11] = *b".          ";
+const DOT_DOT_NAME: [u8; 11] = *b"..         ";
+const ENTRY_SIZE: usize = core::mem::size_of::<DirectoryEntry>();
+// The upper four bits of FAT entries are reserved.
+const FAT_ENTRY_MASK: u32 = 0x0fffffff;
+const FREE_CLUSTER: u32 = 0x0;
+const END_OF_CHAIN: u32 = 0x0fffffff;
+// The first cluster of the data region.
+const FIRST_CLUSTER: usize = 2;
+// Chains, whose read positions are kept.
+const READ_POSITION_AMOUNT: usize = 8;
+// Sizes of files are stored in 32 bits.
+const MAX_FILE_SIZE: usize = u32::MAX as usize;
+// Signatures and fields of the FSInfo sector
+const FS_INFO_LEAD_SIGNATURE: u32 = 0x41615252;
+const FS_INFO_STRUCT_SIGNATURE: u32 = 0x61417272;
+const FS_INFO_TRAIL_SIGNATURE: u32 = 0xaa550000;
+const FS_INFO_STRUCT_OFFSET: usize = 484;
+const FS_INFO_FREE_COUNT_OFFSET: usize = 488;
+const FS_INFO_NEXT_FREE_OFFSET: usize = 492;
+const FS_INFO_TRAIL_OFFSET: usize = 508;
+const FS_INFO_UNKNOWN: u32 = 0xffffffff;
+const SECONDS_PER_DAY: u64 = 86400;
+
+impl DirectoryEntry {
+	/*
+	 * Entry created now.
+	 */
+	fn new(name: [u8; 11], attributes: u8, first_cluster: usize) -> DirectoryEntry {
+		let now = Timestamp::now();
+		let mut entry = DirectoryEntry {
+			name,
+			attributes,
+			reserved: 0,
+			creation_time_tenth: now.tenths,
+			creation_time: now.time,
+			creation_date: now.date,
+			last_access_date: now.date,
+			first_data_cluster_high: 0,
+			modification_time: now.time,
+			modification_date: now.date,
+			first_data_cluster_low: 0,
+			file_size: 0
+		};
+		entry.set_first_cluster(first_cluster);
+		entry
+	}
+	fn first_cluster(&self) -> usize {
+		self.first_data_cluster_low as usize | ((self.first_data_cluster_high as usize) << 16)
+	}
+	fn set_first_cluster(&mut self, cluster: usize) {
+		self.first_data_cluster_low = cluster as u16;
+		self.first_data_cluster_high = (cluster >> 16) as u16;
+	}
+	/*
+	 * Marks the content as modified now and to be archived.
+	 */
+	fn touch(&mut self) {
+		let now = Timestamp::now();
+		self.modification_time = now.time;
+		self.modification_date = now.date;
+		self.last_access_date = now.date;
+		self.attributes |= ATTRIBUTE_ARCHIVE;
+	}
+	fn as_bytes(&self) -> [u8; ENTRY_SIZE] {
+		unsafe {
+			core::mem::transmute::<DirectoryEntry, [u8; ENTRY_SIZE]>(*self)
+		}
+	}
+	fn is_directory(&self) -> bool {
+		self.attributes & ATTRIBUTE_DIRECTORY != 0
+	}
+	/*
+	 * The access date has no time and the root directory has no times at all.
+	 */
+	fn info(&self) -> FileInfo {
+		let creation = Timestamp {
+			date: self.creation_date,
+			time: self.creation_time,
+			tenths: self.creation_time_tenth
+		};
+		let modification = Timestamp {
+			date: self.modification_date,
+			time: self.modification_time,
+			tenths: 0
+		};
+		let access = Timestamp {
+			date: self.last_access_date,
+			time: 0,
+			tenths: 0
+		};
+		FileInfo {
+			size: self.file_size as usize,
+			is_directory: self.is_directory(),
+			// The attributes of the abi have the values of FAT.
+			attributes: self.attributes as u64 & (
+				abi::file::ATTRIBUTE_READ_ONLY |
+				abi::file::ATTRIBUTE_HIDDEN |
+				abi::file::ATTRIBUTE_SYSTEM |
+				abi::file::ATTRIBUTE_ARCHIVE
+			),
+			created: creation.unix_ns(),
+			modified: modification.unix_ns(),
+			accessed: access.unix_ns()
+		}
+	}
+	/*
+	 * The 8.3 name like "NAME.EXT". Bytes outside of ASCII depend
+	 * on the code page, so they´re replaced.
+	 */
+	fn short_name(&self) -> String {
+		let mut raw_name = self.name;
+		if raw_name[0] == ESCAPED_DELETED_MARKER {
+			raw_name[0] = DELETED_ENTRY;
+		}
+		for byte in &mut raw_name {
+			if !byte.is_ascii() {
+				*byte = b'?';
+			}
+		}
+		let base = raw_name[..8].trim_ascii_end();
+		let extension = raw_name[8..].trim_ascii_end();
+		if extension.is_empty() {
+			String::from(base)
+		} else {
+			String::from(base) + String::from(".") + String::from(extension)
+		}
+	}
+	/*
+	 * Checksum of the 8.3 name, which long file name parts store
+	 * to tell, whether they belong to the entry.
+	 */
+	fn checksum(&self) -> u8 {
+		self.name.into_iter().fold(0u8, |sum, byte| sum.rotate_right(1).wrapping_add(byte))
+	}
+}
+
+impl LongNameEntry {
+	/*
+	 * The part with the order of a long file name with the amount of parts.
+	 */
+	fn new(order: usize, parts: usize, checksum: u8, units: &[u16]) -> LongNameEntry {
+		LongNameEntry {
+			order: order as u8 | if order == parts { LAST_LONG_NAME_PART } else { 0 },
+			name1: units[..5].try_into().unwrap(),
+			attributes: ATTRIBUTE_LONG_NAME,
+			kind: 0,
+			checksum,
+			name2: units[5..11].try_into().unwrap(),
+			first_data_cluster_low: 0,
+			name3: units[11..LONG_NAME_PART_LENGTH].try_into().unwrap()
+		}
+	}
+	fn as_bytes(&self) -> [u8; ENTRY_SIZE] {
+		unsafe {
+			core::mem::transmute::<LongNameEntry, [u8; ENTRY_SIZE]>(*self)
+		}
+	}
+	fn units(&self) -> [u16; LONG_NAME_PART_LENGTH] {
+		let (name1, name2, name3) = (self.name1, self.name2, self.name3);
+		let mut units = [0; LONG_NAME_PART_LENGTH];
+		units[..5].copy_from_slice(&name1);
+		units[5..11].copy_from_slice(&name2);
+		units[11..].copy_from_slice(&name3);
+		units
+	}
+}
+
+impl LongName {
+	/*
+	 * Starts a long file name with the part stored first. None, if the
+	 * part isn´t the last one of a name.
+	 */
+	fn start(part: &LongNameEntry) -> Option<LongName> {
+		let order = part.order & LONG_NAME_ORDER_MASK;
+		if part.order & LAST_LONG_NAME_PART == 0 || order == 0 || order as usize > MAX_LONG_NAME_PARTS {
+			return None;
+		}
+		let mut long_name = LongName {
+			units: [0xffff; MAX_LONG_NAME_PARTS * LONG_NAME_PART_LENGTH],
+			checksum: part.checksum,
+			parts: order,
+			next_order: order
+		};
+		long_name.add(part).then_some(long_name)
+	}
+
+	/*
+	 * Adds the next part. Returns false, if it´s out of order
+	 * or belongs to another entry.
+	 */
+	fn add(&mut self, part: &LongNameEntry) -> bool {
+		let order = part.order & !LAST_LONG_NAME_PART;
+		if self.next_order == 0 || order != self.next_order || part.checksum != self.checksum {
+			return false;
+		}
+		let start = (order as usize - 1) * LONG_NAME_PART_LENGTH;
+		self.units[start..start + LONG_NAME_PART_LENGTH].copy_from_slice(&part.units());
+		self.next_order -= 1;
+		true
+	}
+
+	/*
+	 * Decodes the name of the entry, if every part was read and the
+	 * checksum matches. Unpaired surrogates are replaced.
+	 */
+	fn finish(&self, entry: &DirectoryEntry) -> Option<String> {
+		if self.next_order != 0 || self.checksum != entry.checksum() {
+			return None;
+		}
+		let length = self.units.iter().position(|unit| *unit == 0x0000 || *unit == 0xffff).unwrap_or(self.units.len());
+		if length == 0 {
+			return None;
+		}
+		// Each unit takes up to three bytes in UTF-8.
+		let mut bytes = [0u8; MAX_LONG_NAME_PARTS * LONG_NAME_PART_LENGTH * 3];
+		let mut bytes_length = 0;
+		for ch in char::decode_utf16(self.units[..length].iter().copied()) {
+			let ch = ch.unwrap_or(char::REPLACEMENT_CHARACTER);
+			bytes_length += ch.encode_utf8(&mut bytes[bytes_length..]).len();
+		}
+		Some(String::from(&bytes[..bytes_length]))
+	}
+}
+
+impl FatReader {
+	fn new() -> FatReader {
+		FatReader {
+			sector: None
+		}
+	}
+
+	fn entry(&mut self, fat: &FAT32, cluster: usize) -> u32 {
+		let lba = fat.fat_lba + cluster * 4 / SECTOR_SIZE;
+		if self.sector.as_ref().is_none_or(|(sector_lba, _)| *sector_lba != lba) {
+			if let Some((_, sector)) = self.sector.take() {
+				sector.free();
+			}
+			self.sector = Some((lba, read_lba(fat.disk_id, lba)));
+		}
+		let (_, sector) = self.sector.as_ref().expect("FAT sector wasn´t read.");
+		read_u32(&sector[..], cluster * 4 % SECTOR_SIZE) & FAT_ENTRY_MASK
+	}
+
+	fn free(self) {
+		if let Some((_, sector)) = self.sector {
+			sector.free();
+		}
+	}
+}
+
+impl Timestamp {
+	/*
+	 * FAT stores the local time, but the timezone isn´t known, so
+	 * it´s UTC. Times before 1980 can´t be stored.
+	 */
+	fn now() -> Timestamp {
+		let nanoseconds = realtime_ns();
+		let seconds = nanoseconds / NS_PER_SECOND;
+		let (year, month, day) = date_from_days(seconds / SECONDS_PER_DAY);
+		if year < 1980 {
+			return Timestamp {
+				date: 1 << 5 | 1,
+				time: 0,
+				tenths: 0
+			};
+		}
+		let second_of_day = seconds % SECONDS_PER_DAY;
+		Timestamp {
+			date: ((year - 1980).min(127) << 9 | month << 5 | day) as u16,
+			time: ((second_of_day / 3600) << 11 | (second_of_day / 60 % 60) << 5 | (second_of_day % 60 / 2)) as u16,
+			tenths: (second_of_day % 2 * 100 + nanoseconds % NS_PER_SECOND / 10000000) as u8
+		}
+	}
+	/*
+	 * Nanoseconds since the unix epoch, zero if the date isn´t set or invalid.
+	 */
+	fn unix_ns(&self) -> u64 {
+		let year = 1980 + (self.date >> 9) as u64;
+		let month = (self.date >> 5 & 0xf) as u64;
+		let day = (self.date & 0x1f) as u64;
+		if !(1..=12).contains(&month) || day == 0 {
+			return 0;
+		}
+		let hours = (self.time >> 11) as u64;
+		let minutes = (self.time >> 5 & 0x3f) as u64;
+		let seconds = (self.time & 0x1f) as u64 * 2 + self.tenths as u64 / 100;
+		let seconds = ((days_since_epoch(year, month, day) * 24 + hours) * 60 + minutes) * 60 + seconds;
+		seconds * NS_PER_SECOND + (self.tenths as u64 % 100) * (NS_PER_SECOND / 100)
+	}
+}
+
 impl FAT32 {
-	fn read_cluster(&self, cluster: usize) -> Sector {
-		read_lba(self.disk_id, self.boot_sector_info.sectors_per_cluster as usize * (cluster - 2) + self.data_lba)
+	fn cluster_lba(&self, cluster: usize) -> usize {
+		self.boot_sector_info.sectors_per_cluster as usize * (cluster - FIRST_CLUSTER) + self.data_lba
 	}
-	fn read_clusters(&self, cluster: usize, amount: usize) -> Box<[u8]> {
+	fn read_cluster(&self, cluster: usize) -> Box<[u8]> {
 		read_lbas(
 			self.disk_id,
-			self.boot_sector_info.sectors_per_cluster as usize * (cluster - 2) + self.data_lba,
-			self.boot_sector_info.sectors_per_cluster as usize * amount
+			self.cluster_lba(cluster),
+			self.boot_sector_info.sectors_per_cluster as usize
 		)
 	}
+	fn write_cluster(&self, cluster: usize, data: &[u8]) {
+		write_lbas(self.disk_id, self.cluster_lba(cluster), data);
+	}
+	fn is_data_cluster(&self, cluster: usize) -> bool {
+		cluster >= FIRST_CLUSTER && cluster < self.cluster_amount + FIRST_CLUSTER
+	}
+	/*
+	 * Follows the chain of clusters in the File Allocation Table. End of
+	 * chain markers, free and bad clusters end it. Chains can´t be longer
+	 * than the data region, so loops in a broken table end too.
+	 */
+	fn cluster_chain(&self, first_cluster: usize) -> Vec<usize> {
+		let mut chain = Vec::new();
+		let mut fat_reader = FatReader::new();
+		let mut cluster = first_cluster;
+
+		while self.is_data_cluster(cluster) && chain.len() < self.cluster_amount {
+			chain.push_back(cluster);
+			cluster = fat_reader.entry(self, cluster) as usize;
+		}
+		fat_reader.free();
+		chain
+	}
+	/*
+	 * Finds the cluster at the index of the chain. If a read of the
+	 * chain ended before the index, the search continues there.
+	 */
+	fn seek_cluster(&self, fat_reader: &mut FatReader, first_cluster: usize, index: usize) -> Option<ChainPosition> {
+		let cached = (&*self.read_positions.lock()).into_iter()
+			.find(|position| position.first_cluster == first_cluster && position.index <= index)
+			.copied();
+		let mut position = cached.unwrap_or(ChainPosition { first_cluster, index: 0, cluster: first_cluster });
+		if !self.is_data_cluster(position.cluster) {
+			return None;
+		}
+		while position.index < index {
+			position = self.next_position(fat_reader, position)?;
+		}
+		Some(position)
+	}
+	fn next_position(&self, fat_reader: &mut FatReader, position: ChainPosition) -> Option<ChainPosition> {
+		// Chains can´t be longer than the data region, so loops in a broken table end.
+		if position.index + 1 >= self.cluster_amount {
+			return None;
+		}
+		let cluster = fat_reader.entry(self, position.cluster) as usize;
+		self.is_data_cluster(cluster).then_some(ChainPosition { index: position.index + 1, cluster, ..position })
+	}
+	/*
+	 * Keeps the position of a few chains, the oldest one is replaced.
+	 */
+	fn remember_position(&self, position: ChainPosition) {
+		let mut positions = self.read_positions.lock();
+		if let Some(idx) = (&*positions).into_iter().position(|known| known.first_cluster == position.first_cluster) {
+			positions.remove(idx);
+		} else if positions.len() == READ_POSITION_AMOUNT {
+			positions.remove(0);
+		}
+		positions.push_back(position);
+	}
+	/*
+	 * Changes the entry of the cluster in every copy of the FAT.
+	 * The reserved upper bits are kept.
+	 */
+	fn set_fat_entry(&self, cluster: usize, value: u32) {
+		// Positions of chains may be stale, once the table changes.
+		self.read_positions.lock().clear();
+		let offset = cluster * 4 % SECTOR_SIZE;
+		for fat in 0..self.boot_sector_info.fat_amount as usize {
+			let lba = self.fat_lba + fat * self.boot_sector_info.fat_size as usize + cluster * 4 / SECTOR_SIZE;
+			let mut sector = read_lba(self.disk_id, lba);
+			let entry = read_u32(&sector[..], offset) & !FAT_ENTRY_MASK | value & FAT_ENTRY_MASK;
+			write_u32(&mut sector[..], offset, entry);
+			write_lba(self.disk_id, lba, &sector);
+			sector.free();
+		}
+	}
+	/*
+	 * Allocates a zeroed cluster and appends it to the chain, which
+	 * ends with the previous cluster. The search starts at the hint
+	 * of the FSInfo sector.
+	 */
+	fn allocate_cluster(&mut self, previous: Option<usize>) -> Result<usize, FSError> {
+		let mut fat_reader = FatReader::new();
+		let found = (0..self.cluster_amount)
+			.map(|idx| FIRST_CLUSTER + (self.next_free_cluster - FIRST_CLUSTER + idx) % self.cluster_amount)
+			.find(|cluster| fat_reader.entry(self, *cluster) == FREE_CLUSTER);
+		fat_reader.free();
+		let cluster = found.ok_or(FSError::NoSpace)?;
+
+		// The cluster is zeroed before it´s linked, so chains never contain garbage.
+		let zeros = Box::<[u8]>::new_filled(0, self.cluster_size);
+		self.write_cluster(cluster, zeros.as_slice());
+		zeros.free();
+		self.set_fat_entry(cluster, END_OF_CHAIN);
+		if let Some(previous) = previous {
+			self.set_fat_entry(previous, cluster as u32);
+		}
+
+		self.free_clusters = self.free_clusters.map(|free_clusters| free_clusters.saturating_sub(1));
+		self.next_free_cluster = if self.is_data_cluster(cluster + 1) { cluster + 1 } else { FIRST_CLUSTER };
+		self.write_fs_info();
+		Ok(cluster)
+	}
+	/*
+	 * Frees the clusters of the chain starting with the index.
+	 * The cluster before has to end the chain already.
+	 */
+	fn free_chain(&mut self, chain: &Vec<usize>, start: usize) {
+		if start >= chain.len() {
+			return;
+		}
+		for cluster in chain.into_iter().skip(start) {
+			self.set_fat_entry(*cluster, FREE_CLUSTER);
+		}
+		self.free_clusters = self.free_clusters.map(|free_clusters| free_clusters + chain.len() - start);
+		self.write_fs_info();
+	}
+	/*
+	 * Stores the hints in the FSInfo sector, if there´s one.
+	 */
+	fn write_fs_info(&self) {
+		let Some(lba) = self.fs_info_lba else {
+			return;
+		};
+		let mut sector = read_lba(self.disk_id, lba);
+		write_u32(&mut sector[..], FS_INFO_FREE_COUNT_OFFSET, self.free_clusters.map_or(FS_INFO_UNKNOWN, |free_clusters| free_clusters as u32));
+		write_u32(&mut sector[..], FS_INFO_NEXT_FREE_OFFSET, self.next_free_cluster as u32);
+		write_lba(self.disk_id, lba, &sector);
+		sector.free();
+	}
+	/*
+	 * Entries of the directory, which starts at the cluster, named by
+	 * their long file name or by their 8.3 name without one. Deleted
+	 * entries and the volume label are skipped.
+	 */
+	fn read_directory(&self, first_cluster: usize) -> Vec<NamedEntry> {
+		let slots_per_cluster = self.cluster_size / ENTRY_SIZE;
+		let mut entries = Vec::new();
+		// Long file names may continue in the next cluster.
+		let mut long_name: Option<LongName> = None;
+		for (cluster_idx, cluster) in (&self.cluster_chain(first_cluster)).into_iter().enumerate() {
+			let data = self.read_cluster(*cluster);
+			let mut ended = false;
+			for (idx, raw_entry) in data.as_slice().chunks_exact(ENTRY_SIZE).enumerate() {
+				if raw_entry[0] == END_OF_DIRECTORY {
+					ended = true;
+					break;
+				}
+				let entry = unsafe {
+					core::ptr::read_unaligned(raw_entry.as_ptr() as *const DirectoryEntry)
+				};
+				if raw_entry[0] == DELETED_ENTRY {
+					long_name = None;
+				} else if entry.attributes == ATTRIBUTE_LONG_NAME {
+					let part = unsafe {
+						core::ptr::read_unaligned(raw_entry.as_ptr() as *const LongNameEntry)
+					};
+					// Parts, which don´t continue the name, start a new one or are orphans.
+					if !long_name.as_mut().is_some_and(|long_name| long_name.add(&part)) {
+						long_name = LongName::start(&part);
+					}
+				} else {
+					let long_name = long_name.take().and_then(|long_name| Some((long_name.finish(&entry)?, long_name.parts as usize)));
+					if entry.attributes & ATTRIBUTE_VOLUME_ID == 0 {
+						let (name, long_name_parts) = long_name.unwrap_or_else(|| (entry.short_name(), 0));
+						entries.push_back(NamedEntry {
+							name,
+							entry,
+							slot: cluster_idx * slots_per_cluster + idx,
+							long_name_parts
+						});
+					}
+				}
+			}
+			data.free();
+			if ended {
+				break;
+			}
+		}
+		entries
+	}
+	/*
+	 * Changes the slot of the directory, whose clusters are in the chain.
+	 */
+	fn update_slot(&self, chain: &Vec<usize>, slot: usize, update: impl FnOnce(&mut [u8])) {
+		let slots_per_cluster = self.cluster_size / ENTRY_SIZE;
+		let offset = slot % slots_per_cluster * ENTRY_SIZE;
+		let lba = self.cluster_lba(chain[slot / slots_per_cluster]) + offset / SECTOR_SIZE;
+		let sector_offset = offset % SECTOR_SIZE;
+		let mut sector = read_lba(self.disk_id, lba);
+		update(&mut sector[sector_offset..sector_offset + ENTRY_SIZE]);
+		write_lba(self.disk_id, lba, &sector);
+		sector.free();
+	}
+	/*
+	 * Stores the changed entry in its directory.
+	 */
+	fn write_entry(&self, found: &FoundEntry) -> Result<(), FSError> {
+		let directory = found.directory.ok_or(FSError::IsDirectory)?;
+		let raw_entry = found.named_entry.entry.as_bytes();
+		self.update_slot(&self.cluster_chain(directory), found.named_entry.slot, |slot| slot.copy_from_slice(&raw_entry));
+		Ok(())
+	}
+	/*
+	 * Returns the first of the amount of consecutive free slots in the
+	 * directory and whether they´re behind the last entry. Clusters are
+	 * appended, while there aren´t enough.
+	 */
+	fn free_slots(&mut self, directory: usize, amount: usize) -> Result<(usize, bool), FSError> {
+		let slots_per_cluster = self.cluster_size / ENTRY_SIZE;
+		let chain = self.cluster_chain(directory);
+		if chain.empty() {
+			return Err(FSError::Corrupted);
+		}
+		let mut run_start = 0;
+		let mut run_length = 0;
+		// Every slot behind the end of the directory is free.
+		let mut ended = false;
+		for (cluster_idx, cluster) in (&chain).into_iter().enumerate() {
+			let data = self.read_cluster(*cluster);
+			for (idx, raw_entry) in data.as_slice().chunks_exact(ENTRY_SIZE).enumerate() {
+				ended |= raw_entry[0] == END_OF_DIRECTORY;
+				if !ended && raw_entry[0] != DELETED_ENTRY {
+					run_length = 0;
+					continue;
+				}
+				if run_length == 0 {
+					run_start = cluster_idx * slots_per_cluster + idx;
+				}
+				run_length += 1;
+				if run_length == amount {
+					data.free();
+					return Ok((run_start, ended));
+				}
+			}
+			data.free();
+		}
+
+		// Appended clusters are zeroed, so their slots are free and end the directory.
+		if run_length == 0 {
+			run_start = chain.len() * slots_per_cluster;
+		}
+		let mut last_cluster = chain[chain.len() - 1];
+		while run_length < amount {
+			last_cluster = self.allocate_cluster(Some(last_cluster))?;
+			run_length += slots_per_cluster;
+		}
+		Ok((run_start, true))
+	}
+	/*
+	 * Adds the entry with the name to the directory. Names, which don´t
+	 * fit into an 8.3 name, are stored as long file name with an 8.3 alias.
+	 */
+	fn add_entry(&mut self, directory: usize, name: &String, mut entry: DirectoryEntry) -> Result<(), FSError> {
+		let (units, length) = long_name_units(name)?;
+		let name = core::str::from_utf8(name.bytes()).map_err(|_| FSError::InvalidPath)?;
+		let entries = self.read_directory(directory);
+		if (&entries).into_iter().any(|named_entry| named_entry.matches(name)) {
+			return Err(FSError::AlreadyExists);
+		}
+		// Names matching existing 8.3 names exist already, so converted names are unique.
+		entry.name = match exact_short_name(name) {
+			Some(short_name) => short_name,
+			None => short_name_alias(name, &entries)?
+		};
+		// 8.3 names are uppercase, so lowercase names need a long file name too.
+		let parts = if exact_short_name(name).is_some() && !name.bytes().any(|byte| byte.is_ascii_lowercase()) {
+			0
+		} else {
+			length.div_ceil(LONG_NAME_PART_LENGTH)
+		};
+
+		let (first_slot, at_end) = self.free_slots(directory, parts + 1)?;
+		let chain = self.cluster_chain(directory);
+		let checksum = entry.checksum();
+		// The last part is stored first.
+		for (idx, order) in (1..=parts).rev().enumerate() {
+			let part = LongNameEntry::new(order, parts, checksum, &units[(order - 1) * LONG_NAME_PART_LENGTH..order * LONG_NAME_PART_LENGTH]);
+			self.update_slot(&chain, first_slot + idx, |slot| slot.copy_from_slice(&part.as_bytes()));
+		}
+		self.update_slot(&chain, first_slot + parts, |slot| slot.copy_from_slice(&entry.as_bytes()));
+		// Slots behind the end may contain leftovers, so the end is marked again.
+		let next_slot = first_slot + parts + 1;
+		if at_end && next_slot < chain.len() * (self.cluster_size / ENTRY_SIZE) {
+			self.update_slot(&chain, next_slot, |slot| slot[0] = END_OF_DIRECTORY);
+		}
+		Ok(())
+	}
+	/*
+	 * The root directory has no entry, so one is made up.
+	 */
+	fn root_entry(&self) -> NamedEntry {
+		let mut entry = DirectoryEntry {
+			name: [b' '; 11],
+			attributes: ATTRIBUTE_DIRECTORY,
+			reserved: 0,
+			creation_time_tenth: 0,
+			creation_time: 0,
+			creation_date: 0,
+			last_access_date: 0,
+			first_data_cluster_high: 0,
+			modification_time: 0,
+			modification_date: 0,
+			first_data_cluster_low: 0,
+			file_size: 0
+		};
+		entry.set_first_cluster(self.boot_sector_info.root_cluster as usize);
+		NamedEntry {
+			name: String::new(),
+			entry,
+			slot: 0,
+			long_name_parts: 0
+		}
+	}
+	/*
+	 * Walks the directories of the segments from the root directory.
+	 */
+	fn find_entry(&self, segments: &Vec<String>) -> Result<FoundEntry, FSError> {
+		let mut found = FoundEntry {
+			directory: None,
+			named_entry: self.root_entry()
+		};
+		for segment in segments {
+			let name = core::str::from_utf8(segment.bytes()).map_err(|_| FSError::InvalidPath)?;
+			if !found.named_entry.entry.is_directory() {
+				return Err(FSError::NotDirectory);
+			}
+			let directory = found.named_entry.entry.first_cluster();
+			let mut entries = self.read_directory(directory);
+			let idx = (&entries).into_iter()
+				.position(|named_entry| named_entry.matches(name))
+				.ok_or(FSError::FileNotFound)?;
+			found = FoundEntry {
+				directory: Some(directory),
+				named_entry: entries.remove(idx)
+			};
+		}
+		Ok(found)
+	}
+	/*
+	 * Writes the data at the offset of the file. The gap between the
+	 * end of the file and the offset is filled with zeros. Either every
+	 * cluster needed is allocated or none.
+	 */
+	fn write_at(&mut self, found: &mut FoundEntry, offset: usize, data: &[u8]) -> Result<(), FSError> {
+		if found.named_entry.entry.is_directory() {
+			return Err(FSError::IsDirectory);
+		}
+		let size = found.named_entry.entry.file_size as usize;
+		let end = offset.checked_add(data.len()).filter(|end| *end <= MAX_FILE_SIZE).ok_or(FSError::NoSpace)?;
+		let new_size = size.max(end);
+
+		let mut chain = self.cluster_chain(found.named_entry.entry.first_cluster());
+		let old_length = chain.len();
+		if old_length < size.div_ceil(self.cluster_size) {
+			return Err(FSError::Corrupted);
+		}
+		while chain.len() < new_size.div_ceil(self.cluster_size) {
+			let previous = if chain.empty() { None } else { Some(chain[chain.len() - 1]) };
+			match self.allocate_cluster(previous) {
+				Ok(cluster) => {
+					chain.push_back(cluster);
+				},
+				Err(error) => {
+					if old_length != 0 {
+						self.set_fat_entry(chain[old_length - 1], END_OF_CHAIN);
+					}
+					self.free_chain(&chain, old_length);
+					return Err(error);
+				}
+			}
+		}
+		if old_length == 0 && !chain.empty() {
+			found.named_entry.entry.set_first_cluster(chain[0]);
+		}
+
+		let start = offset.min(size);
+		for cluster_idx in start / self.cluster_size..end.div_ceil(self.cluster_size) {
+			let cluster_start = cluster_idx * self.cluster_size;
+			let from = start.max(cluster_start);
+			let to = end.min(cluster_start + self.cluster_size);
+			let zeros_end = offset.clamp(from, to);
+			let mut cluster_data = self.read_cluster(chain[cluster_idx]);
+			let slice = cluster_data.as_slice_mut();
+			slice[from - cluster_start..zeros_end - cluster_start].fill(0);
+			if zeros_end < to {
+				slice[zeros_end - cluster_start..to - cluster_start].copy_from_slice(&data[zeros_end - offset..to - offset]);
+			}
+			self.write_cluster(chain[cluster_idx], cluster_data.as_slice());
+			cluster_data.free();
+		}
+
+		found.named_entry.entry.file_size = new_size as u32;
+		found.named_entry.entry.touch();
+		self.write_entry(found)
+	}
+}
+
+impl NamedEntry {
+	/*
+	 * Names match the long file name or the 8.3 name, ignoring the case.
+	 */
+	fn matches(&self, name: &str) -> bool {
+		self.name.bytes().eq_ignore_ascii_case(name.as_bytes()) ||
+			self.entry.short_name().bytes().eq_ignore_ascii_case(name.as_bytes())
+	}
+	fn is_dot_entry(&self) -> bool {
+		self.entry.name == DOT_NAME || self.entry.name == DOT_DOT_NAME
+	}
 }
 
 impl FileStructure for FAT32 {
@@ -90,63 +907,313 @@ impl FileStructure for FAT32 {
 		if let MountPoint::Disk(disk_id) = mount_point {
 			let boot_sector = read_lba(disk_id, 0);
 			let boot_sector_info = Box::<BootSector>::from_raw_address(boot_sector.physical_address());
-			let data_lba = boot_sector_info.reserved_sector_count as usize + boot_sector_info.fat_amount as usize * boot_sector_info.fat_size as usize;
-			let root_directory_sector = read_lba(disk_id, data_lba);
+			let fat_lba = boot_sector_info.reserved_sector_count as usize;
+			let data_lba = fat_lba + boot_sector_info.fat_amount as usize * boot_sector_info.fat_size as usize;
+			let total_sectors = if boot_sector_info.total_sector_count_16 != 0 {
+				boot_sector_info.total_sector_count_16 as usize
+			} else {
+				boot_sector_info.total_sector_amount as usize
+			};
+			let cluster_amount = total_sectors.saturating_sub(data_lba) / boot_sector_info.sectors_per_cluster.max(1) as usize;
+
+			let mut fs_info_lba = None;
+			let mut free_clusters = None;
+			let mut next_free_cluster = FIRST_CLUSTER;
+			let fs_info_sector = boot_sector_info.fs_info_lba as usize;
+			if fs_info_sector != 0 && fs_info_sector != 0xffff {
+				let fs_info = read_lba(disk_id, fs_info_sector);
+				if read_u32(&fs_info[..], 0) == FS_INFO_LEAD_SIGNATURE &&
+					read_u32(&fs_info[..], FS_INFO_STRUCT_OFFSET) == FS_INFO_STRUCT_SIGNATURE &&
+					read_u32(&fs_info[..], FS_INFO_TRAIL_OFFSET) == FS_INFO_TRAIL_SIGNATURE {
+					fs_info_lba = Some(fs_info_sector);
+					let free_count = read_u32(&fs_info[..], FS_INFO_FREE_COUNT_OFFSET) as usize;
+					if free_count <= cluster_amount {
+						free_clusters = Some(free_count);
+					}
+					let next_free = read_u32(&fs_info[..], FS_INFO_NEXT_FREE_OFFSET) as usize;
+					if next_free >= FIRST_CLUSTER && next_free < cluster_amount + FIRST_CLUSTER {
+						next_free_cluster = next_free;
+					}
+				}
+				fs_info.free();
+			}
+
 			Ok(
 				FAT32 {
-					cluster_size: boot_sector_info.sectors_per_cluster as usize * 512,
+					cluster_size: boot_sector_info.sectors_per_cluster as usize * SECTOR_SIZE,
+					cluster_amount,
 					disk_id,
+					fat_lba,
 					data_lba,
-					root_directory: Box::from_raw_address_sized(root_directory_sector.physical_address(), core::mem::size_of::<DirectoryEntry>() * 0x10),
-					root_directory_sector,
 					boot_sector,
 					boot_sector_info,
+					fs_info_lba,
+					free_clusters,
+					next_free_cluster,
+					read_positions: Mutex::new(Vec::new())
 				}
 			)
 		} else {
 			panic!("Attempt to mount FAT32 without specifing the disk id.");
 		}
 	}
-	fn read(&self, path: FilePath, _: usize, _: usize) -> Result<Box<[u8]>, FSError> {
-		if let Some(fat_path) = get_raw_path(path) {
-			let entry = self.root_directory.as_slice().into_iter().find(
-				|entry| {
-					String::from(entry.name) == fat_path.clone()
-				}
-			).ok_or(FSError::FileNotFound)?;
+	fn read(&self, path: FilePath, offset: usize, len: usize) -> Result<Box<[u8]>, FSError> {
+		let entry = self.find_entry(&path_segments(&path))?.named_entry.entry;
+		let size = entry.file_size as usize;
+		if offset > size {
+			return Err(FSError::OOBRead);
+		}
+		let amount = len.min(size - offset);
+		if amount == 0 {
+			return Ok(Box::null());
+		}
 
-			Ok(
-				self.read_clusters(entry.first_data_cluster_low as usize | ((entry.first_data_cluster_high as usize) << 16), entry.file_size as usize / self.cluster_size + 1)
-			)
-		} else {
-			Err(FSError::InvalidPath)
+		let mut data = Box::<[u8]>::new_sized(amount);
+		let mut fat_reader = FatReader::new();
+		let first_cluster_idx = offset / self.cluster_size;
+		let mut position = self.seek_cluster(&mut fat_reader, entry.first_cluster(), first_cluster_idx);
+		let mut copied = 0;
+		while let Some(current) = position {
+			let cluster_data = self.read_cluster(current.cluster);
+			let start = if current.index == first_cluster_idx { offset % self.cluster_size } else { 0 };
+			let chunk_size = (self.cluster_size - start).min(amount - copied);
+			data.as_slice_mut()[copied..copied + chunk_size].copy_from_slice(&cluster_data.as_slice()[start..start + chunk_size]);
+			cluster_data.free();
+			copied += chunk_size;
+			if copied == amount {
+				self.remember_position(current);
+				break;
+			}
+			position = self.next_position(&mut fat_reader, current);
+		}
+		fat_reader.free();
+		if copied < amount {
+			// The chain is shorter than the size of the file.
+			data.free();
+			return Err(FSError::Corrupted);
 		}
+		Ok(data)
+	}
+	fn stat(&self, path: FilePath) -> Result<FileInfo, FSError> {
+		Ok(self.find_entry(&path_segments(&path))?.named_entry.entry.info())
+	}
+	fn readdir(&self, path: FilePath) -> Result<Vec<DirectoryItem>, FSError> {
+		let entry = self.find_entry(&path_segments(&path))?.named_entry.entry;
+		if !entry.is_directory() {
+			return Err(FSError::NotDirectory);
+		}
+		let mut items = Vec::new();
+		for named_entry in &self.read_directory(entry.first_cluster()) {
+			if named_entry.is_dot_entry() {
+				continue;
+			}
+			items.push_back(DirectoryItem {
+				name: named_entry.name.clone(),
+				info: named_entry.entry.info()
+			});
+		}
+		Ok(items)
+	}
+	fn write(&mut self, path: FilePath, offset: usize, data: &[u8]) -> Result<usize, FSError> {
+		let mut found = self.find_entry(&path_segments(&path))?;
+		self.write_at(&mut found, offset, data)?;
+		Ok(data.len())
+	}
+	/*
+	 * New directories get a cluster with the "." and ".." entries.
+	 */
+	fn create(&mut self, path: FilePath, is_directory: bool) -> Result<(), FSError> {
+		let (parent_segments, name) = split_path(&path)?;
+		let parent = self.find_entry(&parent_segments)?;
+		if !parent.named_entry.entry.is_directory() {
+			return Err(FSError::NotDirectory);
+		}
+		let parent_cluster = parent.named_entry.entry.first_cluster();
+		if !is_directory {
+			return self.add_entry(parent_cluster, &name, DirectoryEntry::new([b' '; 11], ATTRIBUTE_ARCHIVE, 0));
+		}
+
+		let cluster = self.allocate_cluster(None)?;
+		let chain = self.cluster_chain(cluster);
+		// ".." entries refer to the root directory with cluster zero.
+		let dot_dot_cluster = if parent.directory.is_none() { 0 } else { parent_cluster };
+		let dot = DirectoryEntry::new(DOT_NAME, ATTRIBUTE_DIRECTORY, cluster);
+		let dot_dot = DirectoryEntry::new(DOT_DOT_NAME, ATTRIBUTE_DIRECTORY, dot_dot_cluster);
+		self.update_slot(&chain, 0, |slot| slot.copy_from_slice(&dot.as_bytes()));
+		self.update_slot(&chain, 1, |slot| slot.copy_from_slice(&dot_dot.as_bytes()));
+		if let Err(error) = self.add_entry(parent_cluster, &name, DirectoryEntry::new([b' '; 11], ATTRIBUTE_DIRECTORY, cluster)) {
+			self.free_chain(&chain, 0);
+			return Err(error);
+		}
+		Ok(())
+	}
+	fn truncate(&mut self, path: FilePath, size: usize) -> Result<(), FSError> {
+		let mut found = self.find_entry(&path_segments(&path))?;
+		let entry = &mut found.named_entry.entry;
+		if entry.is_directory() {
+			return Err(FSError::IsDirectory);
+		}
+		if size >= entry.file_size as usize {
+			return self.write_at(&mut found, size, &[]);
+		}
+
+		let chain = self.cluster_chain(entry.first_cluster());
+		let kept_clusters = size.div_ceil(self.cluster_size);
+		if kept_clusters == 0 {
+			entry.set_first_cluster(0);
+		} else if kept_clusters < chain.len() {
+			self.set_fat_entry(chain[kept_clusters - 1], END_OF_CHAIN);
+		}
+		self.free_chain(&chain, kept_clusters);
+		entry.file_size = size as u32;
+		entry.touch();
+		self.write_entry(&found)
+	}
+	/*
+	 * The entry is removed before its clusters are freed.
+	 */
+	fn remove(&mut self, path: FilePath) -> Result<(), FSError> {
+		let found = self.find_entry(&path_segments(&path))?;
+		// The root directory can´t be removed.
+		let directory = found.directory.ok_or(FSError::InvalidPath)?;
+		let named_entry = &found.named_entry;
+		let first_cluster = named_entry.entry.first_cluster();
+		if named_entry.entry.is_directory() && (&self.read_directory(first_cluster)).into_iter().any(|entry| !entry.is_dot_entry()) {
+			return Err(FSError::DirectoryNotEmpty);
+		}
+
+		let directory_chain = self.cluster_chain(directory);
+		for slot in named_entry.slot - named_entry.long_name_parts..=named_entry.slot {
+			self.update_slot(&directory_chain, slot, |slot| slot[0] = DELETED_ENTRY);
+		}
+		if first_cluster != 0 {
+			let chain = self.cluster_chain(first_cluster);
+			self.free_chain(&chain, 0);
+		}
+		Ok(())
 	}
 }
 
-fn get_raw_path(path: FilePath) -> Option<String> {
-	fn segment_to_raw(segment: String) -> Option<String> {
-		if segment == "".into() {
-			return None;
+/*
+ * Segments of the path without empty ones. "." and ".." are resolved
+ * beforehand, so they never refer to entries.
+ */
+fn path_segments(path: &FilePath) -> Vec<String> {
+	let mut segments = Vec::new();
+	for segment in &path.segments() {
+		if *segment == "".into() || *segment == ".".into() {
+			continue;
 		}
-		let uppersegment = segment.upper();
-		let splitted = uppersegment.split('.');
+		if *segment == "..".into() {
+			if !segments.empty() {
+				segments.remove(segments.len() - 1);
+			}
+			continue;
+		}
+		segments.push_back(segment.clone());
+	}
+	segments
+}
 
-		match splitted.len() {
-			1 => Some(uppersegment.padded(11, ' ')),
-			2 => if splitted[1].len() <= 3 {
-				Some(splitted[0].padded(8, ' ') + splitted[1].clone())
-			} else {
-				None
-			},
-			_ => None
+/*
+ * Splits the path into the segments of the directory containing
+ * the file and its name.
+ */
+fn split_path(path: &FilePath) -> Result<(Vec<String>, String), FSError> {
+	let mut segments = path_segments(path);
+	if segments.empty() {
+		return Err(FSError::InvalidPath);
+	}
+	let name = segments.remove(segments.len() - 1);
+	Ok((segments, name))
+}
+
+/*
+ * The name in UTF-16 like it´s stored in long file name parts and its
+ * length. Names, which can´t be stored, are invalid.
+ */
+fn long_name_units(name: &String) -> Result<([u16; MAX_LONG_NAME_PARTS * LONG_NAME_PART_LENGTH], usize), FSError> {
+	let name = core::str::from_utf8(name.bytes()).map_err(|_| FSError::InvalidPath)?;
+	if name.ends_with('.') || name.ends_with(' ') || name.chars().any(|ch| ch < ' ' || INVALID_NAME_CHARACTERS.contains(ch)) {
+		return Err(FSError::InvalidPath);
+	}
+	let mut units = [0xffff; MAX_LONG_NAME_PARTS * LONG_NAME_PART_LENGTH];
+	let mut length = 0;
+	for unit in name.encode_utf16() {
+		if length == MAX_LONG_NAME_LENGTH {
+			return Err(FSError::InvalidPath);
+		}
+		units[length] = unit;
+		length += 1;
+	}
+	// Names, which don´t fill their last part, end with a zero.
+	if length % LONG_NAME_PART_LENGTH != 0 {
+		units[length] = 0x0000;
+	}
+	Ok((units, length))
+}
+
+fn is_short_name_character(byte: u8) -> bool {
+	byte.is_ascii_uppercase() || byte.is_ascii_digit() || SHORT_NAME_SPECIAL_CHARACTERS.contains(&byte)
+}
+
+/*
+ * The 8.3 name of names, which fit into one except for the case.
+ */
+fn exact_short_name(name: &str) -> Option<[u8; 11]> {
+	let (base, extension) = name.split_once('.').unwrap_or((name, ""));
+	if base.is_empty() || base.len() > 8 || extension.len() > 3 ||
+		!base.bytes().chain(extension.bytes()).all(|byte| is_short_name_character(byte.to_ascii_uppercase())) {
+		return None;
+	}
+	let mut short_name = [b' '; 11];
+	short_name[..base.len()].copy_from_slice(base.as_bytes());
+	short_name[8..8 + extension.len()].copy_from_slice(extension.as_bytes());
+	short_name.make_ascii_uppercase();
+	Some(short_name)
+}
+
+/*
+ * Generates an 8.3 alias like "LONGNA~1.TXT" for a long file name,
+ * which differs from the 8.3 names of the other entries.
+ */
+fn short_name_alias(name: &str, entries: &Vec<NamedEntry>) -> Result<[u8; 11], FSError> {
+	fn convert(part: &str, limit: usize) -> ([u8; 8], usize) {
+		let mut converted = [b' '; 8];
+		let mut length = 0;
+		for ch in part.chars().filter(|ch| *ch != ' ' && *ch != '.').take(limit) {
+			let ch = ch.to_ascii_uppercase();
+			converted[length] = if ch.is_ascii() && is_short_name_character(ch as u8) { ch as u8 } else { b'_' };
+			length += 1;
 		}
+		(converted, length)
 	}
 
-	let segments = path.segments();
-	if segments.len() != 2 {
-		None
-	} else {
-		segment_to_raw(segments[1].clone())
+	let name = name.trim_start_matches('.');
+	let (base, extension) = name.rsplit_once('.').unwrap_or((name, ""));
+	let (base, base_length) = convert(base, 8);
+	let (extension, extension_length) = convert(extension, 3);
+	for number in 1..1000000u32 {
+		let digits = number.ilog10() as usize + 1;
+		let kept = base_length.min(7 - digits);
+		let mut short_name = [b' '; 11];
+		short_name[..kept].copy_from_slice(&base[..kept]);
+		short_name[kept] = b'~';
+		for digit in 0..digits {
+			short_name[kept + digits - digit] = b'0' + (number / 10u32.pow(digit as u32) % 10) as u8;
+		}
+		short_name[8..8 + extension_length].copy_from_slice(&extension[..extension_length]);
+		if !entries.into_iter().any(|named_entry| named_entry.entry.name == short_name) {
+			return Ok(short_name);
+		}
 	}
+	Err(FSError::NoSpace)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+	u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn write_u32(bytes: &mut [u8], offset: usize, value: u32) {
+	bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
 }
@@ -4,7 +4,13 @@ use crate::std::{
 	Vec
 };
 use super::FSError;
+use abi::file::{
+	FileStat,
+	KIND_DIRECTORY,
+	KIND_FILE
+};
 
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum MountPoint {
 	Disk(usize),
 	Standalone
@@ -13,30 +19,89 @@ pub enum MountPoint {
 pub trait FileStructure {
 	fn mount(mount_point: MountPoint) -> Result<Self, FSError> where Self: Sized;
 	fn read(&self, path: FilePath, offset: usize, len: usize) -> Result<Box<[u8]>, FSError>;
+	fn stat(&self, path: FilePath) -> Result<FileInfo, FSError>;
+	/*
+	 * Entries of the directory without "." and "..".
+	 */
+	fn readdir(&self, path: FilePath) -> Result<Vec<DirectoryItem>, FSError>;
+	/*
+	 * Writes the data at the offset of the file and returns
+	 * the amount of bytes written.
+	 */
+	fn write(&mut self, _path: FilePath, _offset: usize, _data: &[u8]) -> Result<usize, FSError> {
+		Err(FSError::ReadOnly)
+	}
+	/*
+	 * Creates an empty file or directory. The directory
+	 * containing it has to exist.
+	 */
+	fn create(&mut self, _path: FilePath, _is_directory: bool) -> Result<(), FSError> {
+		Err(FSError::ReadOnly)
+	}
+	/*
+	 * Changes the size of the file. Files grow with zeros.
+	 */
+	fn truncate(&mut self, _path: FilePath, _size: usize) -> Result<(), FSError> {
+		Err(FSError::ReadOnly)
+	}
+	/*
+	 * Removes the file or the empty directory.
+	 */
+	fn remove(&mut self, _path: FilePath) -> Result<(), FSError> {
+		Err(FSError::ReadOnly)
+	}
+}
+
+/*
+ * Attributes are the ones of abi::file, times are nanoseconds
+ * since the unix epoch or zero if they´re unknown.
+ */
+#[derive(Clone, Copy)]
+pub struct FileInfo {
+	pub size: usize,
+	pub is_directory: bool,
+	pub attributes: u64,
+	pub created: u64,
+	pub modified: u64,
+	pub accessed: u64
 }
 
-pub enum FilePath {
-	Unix(String),
-	DOS(String)
+pub struct DirectoryItem {
+	pub name: String,
+	pub info: FileInfo
 }
 
+/*
+ * Path inside of a filesystem starting at its root. Paths
+ * of the namespace are resolved to these by virt::fs::resolve.
+ */
+#[derive(Clone)]
+pub struct FilePath(String);
+
 impl MountPoint {
 	pub fn from_disk(disk_id: usize) -> MountPoint {
 		MountPoint::Disk(disk_id)
 	}
 }
 
-impl FilePath {
-	pub const fn new_unix(path: String) -> FilePath {
-		FilePath::Unix(path)
+impl FileInfo {
+	pub fn stat(&self) -> FileStat {
+		FileStat {
+			size: self.size as u64,
+			kind: if self.is_directory { KIND_DIRECTORY } else { KIND_FILE },
+			attributes: self.attributes,
+			created: self.created,
+			modified: self.modified,
+			accessed: self.accessed
+		}
 	}
-	pub const fn new_dos(path: String) -> FilePath {
-		FilePath::DOS(path)
+}
+
+impl FilePath {
+	pub const fn new(path: String) -> FilePath {
+		FilePath(path)
 	}
 	pub fn segments(&self) -> Vec<String> {
-		match self {
-			FilePath::DOS(s) => (String::from("/") + s.clone()).split('/'),
-			FilePath::Unix(s) => s.split('/')
-		}
+		self.0.split('/')
 	}
 }
@@ -5,6 +5,8 @@ mod mount;
 
 pub use filestructure::{
 	FileStructure,
+	FileInfo,
+	DirectoryItem,
 	FilePath,
 	MountPoint
 };
@@ -18,17 +20,56 @@ use crate::std::{
 	Vec,
 	VecBase,
 	Mutex,
-	MutexGuard
+	MutexGuard,
+	KernelError
 };
 
 pub enum FSError {
 	OOBRead, // Offset is greater than the length of the file while reading.
 	FileNotFound,
-	InvalidPath
+	InvalidPath,
+	ReadOnly, // The filesystem doesn´t support writing.
+	UnknownFileSystem,
+	Corrupted, // The structures on the disk contradict each other.
+	AlreadyExists,
+	IsDirectory,
+	NotDirectory,
+	DirectoryNotEmpty,
+	NoSpace,
+	Busy // The path is a mount point or the disk is mounted already.
+}
+
+impl From<FSError> for KernelError {
+	fn from(error: FSError) -> KernelError {
+		match error {
+			FSError::OOBRead => KernelError::InvalidArgument,
+			FSError::FileNotFound => KernelError::NotFound,
+			FSError::InvalidPath => KernelError::InvalidArgument,
+			FSError::ReadOnly => KernelError::ReadOnlyFilesystem,
+			FSError::UnknownFileSystem => KernelError::NoDevice,
+			FSError::Corrupted => KernelError::InputOutput,
+			FSError::AlreadyExists => KernelError::AlreadyExists,
+			FSError::IsDirectory => KernelError::IsDirectory,
+			FSError::NotDirectory => KernelError::NotDirectory,
+			FSError::DirectoryNotEmpty => KernelError::DirectoryNotEmpty,
+			FSError::NoSpace => KernelError::NoSpace,
+			FSError::Busy => KernelError::Busy
+		}
+	}
+}
+
+/*
+ * Filesystem mounted at the directory of the path in the namespace.
+ */
+struct Mount {
+	// Absolute like the results of absolute_path.
+	path: String,
+	source: MountPoint,
+	filesystem: usize
 }
 
 static FILE_SYSTEMS: Mutex<Vec<Mutex<Box<dyn FileStructure>>>> = Mutex::new(Vec::new());
-static MOUNTPOINTS: Mutex<Vec<MountPoint>> = Mutex::new(Vec::new());
+static MOUNTS: Mutex<Vec<Mount>> = Mutex::new(Vec::new());
 
 pub fn readresult_to_str(readresult: Result<Box<[u8]>, FSError>) -> Result<String, FSError> {
 	if let Ok(result) = readresult {
@@ -38,20 +79,104 @@ pub fn readresult_to_str(readresult: Result<Box<[u8]>, FSError>) -> Result<Strin
 	}
 }
 
-pub fn mount(mountpoint: MountPoint) -> usize {
-	if let Some(fs) = mount::mount(mountpoint) {
-		let mut fslock = FILE_SYSTEMS.lock();
-		fslock.push_back(Mutex::new(fs));
-		fslock.len() - 1
+/*
+ * Mounts the filesystem of the mount point at the directory of the
+ * path. The first filesystem is mounted at the root "/", which
+ * contains the directories of the others. Disks are mounted once.
+ */
+pub fn mount(mountpoint: MountPoint, path: &String) -> Result<(), FSError> {
+	let path = absolute_path(&String::from("/"), path);
+	// Locked throughout, so nobody else mounts at the path meanwhile.
+	let mut mounts = MOUNTS.lock();
+	if (&*mounts).into_iter().any(|mount| mount.path == path || (mount.source == mountpoint && mountpoint != MountPoint::Standalone)) {
+		return Err(FSError::Busy);
+	}
+	if !mounts.empty() || path != String::from("/") {
+		let (filesystem, file_path) = find_mount(&mounts, &path).ok_or(FSError::FileNotFound)?;
+		if !self::filesystem(filesystem)?.stat(file_path)?.is_directory {
+			return Err(FSError::NotDirectory);
+		}
+	}
+	let filesystem = mount::mount(mountpoint).ok_or(FSError::UnknownFileSystem)?;
+	let filesystem = {
+		let mut filesystems = FILE_SYSTEMS.lock();
+		filesystems.push_back(Mutex::new(filesystem));
+		filesystems.len() - 1
+	};
+	mounts.push_back(Mount {
+		path,
+		source: mountpoint,
+		filesystem
+	});
+	Ok(())
+}
+
+/*
+ * The filesystem containing the absolute path and the path inside of it.
+ */
+pub fn resolve(path: &String) -> Result<(usize, FilePath), FSError> {
+	find_mount(&MOUNTS.lock(), path).ok_or(FSError::FileNotFound)
+}
+
+pub fn is_mount_point(path: &String) -> bool {
+	(&*MOUNTS.lock()).into_iter().any(|mount| mount.path == *path)
+}
+
+/*
+ * Absolute path without empty segments, "." and "..". Relative paths
+ * start at the directory, which is absolute. ".." of the root is the root.
+ */
+pub fn absolute_path(directory: &String, path: &String) -> String {
+	let path = if path.bytes().first() == Some(&b'/') {
+		path.clone()
 	} else {
-		usize::MAX
+		directory + &(String::from("/") + path.clone())
+	};
+	let mut segments: Vec<String> = Vec::new();
+	for segment in &path.split('/') {
+		if *segment == "".into() || *segment == ".".into() {
+			continue;
+		}
+		if *segment == "..".into() {
+			if !segments.empty() {
+				segments.remove(segments.len() - 1);
+			}
+			continue;
+		}
+		segments.push_back(segment.clone());
 	}
+	String::from("/") + String::from("/").join(segments.into_iter())
+}
+
+/*
+ * Mount points may be nested, the innermost one containing the path is used.
+ */
+fn find_mount(mounts: &Vec<Mount>, path: &String) -> Option<(usize, FilePath)> {
+	let mount = mounts.into_iter()
+		.filter(|mount| {
+			let prefix = mount.path.bytes();
+			// Only the root ends with a slash.
+			prefix == b"/" ||
+				(path.bytes().starts_with(prefix) && path.bytes().get(prefix.len()).is_none_or(|byte| *byte == b'/'))
+		})
+		.max_by_key(|mount| mount.path.len())?;
+	let file_path = if mount.path.len() == 1 {
+		path.clone()
+	} else if path.len() == mount.path.len() {
+		String::from("/")
+	} else {
+		path.slice(mount.path.len()..path.len())?
+	};
+	Some((mount.filesystem, FilePath::new(file_path)))
 }
 
 pub fn filesystems() -> Vec<usize> {
 	(0..FILE_SYSTEMS.len()).collect()
 }
 
-pub fn filesystem(id: usize) -> MutexGuard<'static, Box<dyn FileStructure>> {
-	FILE_SYSTEMS[id].lock()
+pub fn filesystem(id: usize) -> Result<MutexGuard<'static, Box<dyn FileStructure>>, FSError> {
+	if id >= FILE_SYSTEMS.len() {
+		return Err(FSError::UnknownFileSystem);
+	}
+	Ok(FILE_SYSTEMS[id].lock())
 }
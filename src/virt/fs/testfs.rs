@@ -1,11 +1,14 @@
 use super::{
 	FileStructure,
+	FileInfo,
+	DirectoryItem,
 	FilePath,
 	FSError,
 	MountPoint
 };
 use crate::std::{
-	Box
+	Box,
+	Vec
 };
 
 pub struct TestFS;
@@ -20,6 +23,21 @@ impl FileStructure for TestFS {
 			TestFS {}
 		)
 	}
+	fn stat(&self, _: FilePath) -> Result<FileInfo, FSError> {
+		Ok(
+			FileInfo {
+				size: Self::TESTFS_CONTENT.len(),
+				is_directory: false,
+				attributes: 0,
+				created: 0,
+				modified: 0,
+				accessed: 0
+			}
+		)
+	}
+	fn readdir(&self, _: FilePath) -> Result<Vec<DirectoryItem>, FSError> {
+		Err(FSError::NotDirectory)
+	}
 	fn read(&self, _: FilePath, offset: usize, len: usize) -> Result<Box<[u8]>, FSError> {
 		if offset >= Self::TESTFS_CONTENT.len() {
 			return Result::Err(FSError::OOBRead);
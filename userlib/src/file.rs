@@ -0,0 +1,160 @@
+use abi::syscall;
+use super::Result;
+use super::raw::checked_syscall;
+
+pub use abi::file::*;
+
+/*
+ * Reads the amount of bytes at the offset of the file at the path.
+ * Returns the address of a new buffer with the content.
+ */
+pub fn read_file(path: &str, amount: usize, offset: usize) -> Result<u64> {
+	unsafe {
+		checked_syscall(syscall::READ_FILE, [path.as_ptr() as u64, path.len() as u64, amount as u64, offset as u64, 0, 0])
+	}
+}
+
+/*
+ * Opens the file at the path with the flags and returns its descriptor.
+ */
+pub fn open(path: &str, flags: u64) -> Result<u64> {
+	unsafe {
+		checked_syscall(syscall::OPEN, [path.as_ptr() as u64, path.len() as u64, flags, 0, 0, 0])
+	}
+}
+
+pub fn mkdir(path: &str) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::MKDIR, [path.as_ptr() as u64, path.len() as u64, 0, 0, 0, 0])?;
+	}
+	Ok(())
+}
+
+pub fn unlink(path: &str) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::UNLINK, [path.as_ptr() as u64, path.len() as u64, 0, 0, 0, 0])?;
+	}
+	Ok(())
+}
+
+/*
+ * Removes the directory, which has to be empty.
+ */
+pub fn rmdir(path: &str) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::RMDIR, [path.as_ptr() as u64, path.len() as u64, 0, 0, 0, 0])?;
+	}
+	Ok(())
+}
+
+/*
+ * Returns the amount of bytes read, which is zero at the end of the file.
+ */
+pub fn read(fd: u64, buffer: &mut [u8]) -> Result<usize> {
+	let amount = unsafe {
+		checked_syscall(syscall::READ, [fd, buffer.as_mut_ptr() as u64, buffer.len() as u64, 0, 0, 0])?
+	};
+	Ok(amount as usize)
+}
+
+pub fn write(fd: u64, buffer: &[u8]) -> Result<usize> {
+	let amount = unsafe {
+		checked_syscall(syscall::WRITE, [fd, buffer.as_ptr() as u64, buffer.len() as u64, 0, 0, 0])?
+	};
+	Ok(amount as usize)
+}
+
+/*
+ * Moves the offset relative to the origin (SEEK_SET, SEEK_CUR
+ * or SEEK_END) and returns the new offset.
+ */
+pub fn lseek(fd: u64, offset: i64, origin: u64) -> Result<u64> {
+	unsafe {
+		checked_syscall(syscall::LSEEK, [fd, offset as u64, origin, 0, 0, 0])
+	}
+}
+
+pub fn close(fd: u64) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::CLOSE, [fd, 0, 0, 0, 0, 0])?;
+	}
+	Ok(())
+}
+
+/*
+ * Changes the size of the file. Files grow with zeros.
+ */
+pub fn ftruncate(fd: u64, size: u64) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::FTRUNCATE, [fd, size, 0, 0, 0, 0])?;
+	}
+	Ok(())
+}
+
+pub fn fstat(fd: u64) -> Result<FileStat> {
+	let mut stat = FileStat::default();
+	unsafe {
+		checked_syscall(syscall::FSTAT, [fd, &mut stat as *mut FileStat as u64, 0, 0, 0, 0])?;
+	}
+	Ok(stat)
+}
+
+pub fn stat(path: &str) -> Result<FileStat> {
+	let mut stat = FileStat::default();
+	unsafe {
+		checked_syscall(syscall::STAT, [path.as_ptr() as u64, path.len() as u64, &mut stat as *mut FileStat as u64, 0, 0, 0])?;
+	}
+	Ok(stat)
+}
+
+/*
+ * Lists the entries of the directory starting with the one at the index
+ * into the records. Returns the amount of records filled, which is zero
+ * after the last entry.
+ */
+pub fn readdir(path: &str, records: &mut [DirectoryRecord], start: usize) -> Result<usize> {
+	unsafe {
+		checked_syscall(syscall::READDIR, [path.as_ptr() as u64, path.len() as u64, records.as_mut_ptr() as u64, records.len() as u64, start as u64, 0])
+			.map(|written| written as usize)
+	}
+}
+
+pub fn chdir(path: &str) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::CHDIR, [path.as_ptr() as u64, path.len() as u64, 0, 0, 0, 0])?;
+	}
+	Ok(())
+}
+
+/*
+ * Writes the absolute path of the working directory to the buffer,
+ * if it fits. Returns the length of the path.
+ */
+pub fn getcwd(buffer: &mut [u8]) -> Result<usize> {
+	unsafe {
+		checked_syscall(syscall::GETCWD, [buffer.as_mut_ptr() as u64, buffer.len() as u64, 0, 0, 0, 0])
+			.map(|length| length as usize)
+	}
+}
+
+/*
+ * Mounts the filesystem of the disk at the directory of the path.
+ * Only privileged processes may mount.
+ */
+pub fn mount(disk: u64, path: &str) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::MOUNT, [disk, path.as_ptr() as u64, path.len() as u64, 0, 0, 0])?;
+	}
+	Ok(())
+}
+
+/*
+ * Creates a pipe. Returns the descriptors of its read and its write end.
+ */
+pub fn pipe() -> Result<[u64; 2]> {
+	let mut fds = [0u64; 2];
+	unsafe {
+		checked_syscall(syscall::PIPE, [fds.as_mut_ptr() as u64, 0, 0, 0, 0, 0])?;
+	}
+	Ok(fds)
+}
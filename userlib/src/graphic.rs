@@ -0,0 +1,32 @@
+use abi::syscall;
+use super::Result;
+use super::raw::{
+	syscall,
+	checked_syscall
+};
+
+pub fn claim_graphic_manager() {
+	unsafe {
+		syscall(syscall::CLAIM_GRAPHIC_MANAGER, [0; 6]);
+	}
+}
+
+/*
+ * Creates a layer with the z coordinate and returns its id.
+ * Requires the graphic manager flag.
+ */
+pub fn add_layer(z: u8) -> Result<u64> {
+	unsafe {
+		checked_syscall(syscall::ADD_LAYER, [z as u64, 0, 0, 0, 0, 0])
+	}
+}
+
+/*
+ * Draws a rectangle with the color (0xbbggrr) in the layer.
+ */
+pub fn draw_rect(layer: u64, position: (u64, u64), size: (u64, u64), color: u32) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::DRAW_RECT, [layer, position.0, position.1, size.0, size.1, color as u64])?;
+	}
+	Ok(())
+}
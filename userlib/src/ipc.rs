@@ -0,0 +1,67 @@
+use abi::syscall;
+use super::Result;
+use super::raw::checked_syscall;
+
+pub use abi::ipc::*;
+
+/*
+ * Creates a port, which the current process receives messages of.
+ */
+pub fn port_create(name: &str) -> Result<u64> {
+	unsafe {
+		checked_syscall(syscall::PORT_CREATE, [name.as_ptr() as u64, name.len() as u64, 0, 0, 0, 0])
+	}
+}
+
+pub fn port_lookup(name: &str) -> Result<u64> {
+	unsafe {
+		checked_syscall(syscall::PORT_LOOKUP, [name.as_ptr() as u64, name.len() as u64, 0, 0, 0, 0])
+	}
+}
+
+pub fn port_destroy(port: u64) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::PORT_DESTROY, [port, 0, 0, 0, 0, 0])?;
+	}
+	Ok(())
+}
+
+/*
+ * Queues the message without waiting for a reply.
+ */
+pub fn send(port: u64, message: &[u8]) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::PORT_SEND, [port, message.as_ptr() as u64, message.len() as u64, 0, 0, 0])?;
+	}
+	Ok(())
+}
+
+/*
+ * Sends the message and waits for the reply. Returns the
+ * length of the reply, which is truncated to the buffer.
+ */
+pub fn call(port: u64, message: &[u8], reply: &mut [u8]) -> Result<usize> {
+	let length = unsafe {
+		checked_syscall(syscall::PORT_CALL, [port, message.as_ptr() as u64, message.len() as u64, reply.as_mut_ptr() as u64, reply.len() as u64, 0])?
+	};
+	Ok(length as usize)
+}
+
+/*
+ * Waits for a message at the port. Returns the length copied to the
+ * buffer and the info, whose id is passed to reply for calls.
+ */
+pub fn receive(port: u64, buffer: &mut [u8]) -> Result<(usize, MessageInfo)> {
+	let mut info = MessageInfo::default();
+	let length = unsafe {
+		checked_syscall(syscall::PORT_RECEIVE, [port, buffer.as_mut_ptr() as u64, buffer.len() as u64, &mut info as *mut MessageInfo as u64, 0, 0])?
+	};
+	Ok((length as usize, info))
+}
+
+pub fn reply(port: u64, id: u64, message: &[u8]) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::PORT_REPLY, [port, id, message.as_ptr() as u64, message.len() as u64, 0, 0])?;
+	}
+	Ok(())
+}
@@ -0,0 +1,131 @@
+/*
+ * Typed wrappers for the syscalls of the kernel, so user programs
+ * can be written in Rust. Numbers, records and constants come from
+ * the abi crate, which the kernel uses too.
+ */
+#![no_std]
+
+pub mod raw;
+pub mod process;
+pub mod thread;
+pub mod signal;
+pub mod time;
+pub mod graphic;
+pub mod mem;
+pub mod file;
+pub mod ipc;
+pub mod startup;
+
+pub use abi;
+
+use abi::syscall;
+use abi::process::{
+	ExecArguments,
+	StringRef
+};
+use raw::{
+	syscall,
+	checked_syscall
+};
+use core::fmt;
+
+/*
+ * Error code returned by a syscall. The code is kept, because
+ * newer kernels may return codes, which abi::error::Error lacks.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Error(pub u64);
+
+impl Error {
+	pub fn kind(&self) -> Option<abi::error::Error> {
+		abi::error::Error::from_code(self.0)
+	}
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+pub fn exit(exit_code: u64) -> ! {
+	unsafe {
+		syscall(syscall::EXIT, [exit_code, 0, 0, 0, 0, 0]);
+	}
+	unreachable!()
+}
+
+pub fn shutdown() -> ! {
+	unsafe {
+		syscall(syscall::SHUTDOWN, [0; 6]);
+	}
+	unreachable!()
+}
+
+pub fn reboot() -> ! {
+	unsafe {
+		syscall(syscall::REBOOT, [0; 6]);
+	}
+	unreachable!()
+}
+
+pub fn print(text: &str) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::PRINT, [text.as_ptr() as u64, text.len() as u64, 0, 0, 0, 0])?;
+	}
+	Ok(())
+}
+
+/*
+ * Executes the ELF at the path. Returns its pid.
+ */
+pub fn exec(path: &str) -> Result<u64> {
+	unsafe {
+		checked_syscall(syscall::EXEC, [path.as_ptr() as u64, path.len() as u64, 0, 0, 0, 0])
+	}
+}
+
+/*
+ * Executes the ELF with the arguments and environment variables
+ * ("NAME=value"), which it finds on its stack (see startup). The
+ * descriptors become its stdin, stdout and stderr, NO_FILE leaves
+ * one closed. Returns its pid.
+ */
+pub fn exec_with(path: &str, arguments: &[StringRef], environment: &[StringRef], files: &[u64]) -> Result<u64> {
+	let exec_arguments = ExecArguments {
+		arguments: arguments.as_ptr() as u64,
+		argument_amount: arguments.len() as u64,
+		environment: environment.as_ptr() as u64,
+		environment_amount: environment.len() as u64
+	};
+	unsafe {
+		checked_syscall(syscall::EXEC, [
+			path.as_ptr() as u64,
+			path.len() as u64,
+			&exec_arguments as *const ExecArguments as u64,
+			files.as_ptr() as u64,
+			files.len() as u64,
+			0
+		])
+	}
+}
+
+/*
+ * Writes formatted text to the console of the kernel.
+ */
+pub struct Console;
+
+impl fmt::Write for Console {
+	fn write_str(&mut self, string: &str) -> fmt::Result {
+		print(string).map_err(|_| fmt::Error)
+	}
+}
+
+#[macro_export]
+macro_rules! print {
+	($($args: tt)+) => {{
+		let _ = core::fmt::Write::write_fmt(&mut $crate::Console, format_args!($($args)+));
+	}};
+}
+#[macro_export]
+macro_rules! println {
+	($($args: tt)+) => {{
+		let _ = core::fmt::Write::write_fmt(&mut $crate::Console, format_args!("{}\n", format_args!($($args)+)));
+	}};
+}
@@ -0,0 +1,107 @@
+use abi::syscall;
+use super::Result;
+use super::raw::checked_syscall;
+
+pub use abi::memory::*;
+
+pub fn allocate(size: usize) -> Result<*mut u8> {
+	let address = unsafe {
+		checked_syscall(syscall::ALLOCATE, [size as u64, 0, 0, 0, 0, 0])?
+	};
+	Ok(address as *mut u8)
+}
+
+/**
+ * Frees an allocation with the size, it was allocated with.
+ *
+ * # Safety
+ * The address has to be returned by allocate with the size and
+ * nothing may use the allocation afterwards.
+ */
+pub unsafe fn free(address: *mut u8, size: usize) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::FREE, [address as u64, size as u64, 0, 0, 0, 0])?;
+	}
+	Ok(())
+}
+
+/*
+ * Maps zeroed memory with the protection (PROT_*) at the address
+ * or anywhere, if it´s null. Returns the address of the mapping.
+ */
+pub fn mmap(address: *mut u8, size: usize, protection: u64) -> Result<*mut u8> {
+	let address = unsafe {
+		checked_syscall(syscall::MMAP, [address as u64, size as u64, protection, 0, 0, 0])?
+	};
+	Ok(address as *mut u8)
+}
+
+/**
+ * Unmaps the whole mapping at the address with the size, it was mapped with.
+ *
+ * # Safety
+ * Nothing may still reference the range, it isn´t mapped afterwards.
+ */
+pub unsafe fn munmap(address: *mut u8, size: usize) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::MUNMAP, [address as u64, size as u64, 0, 0, 0, 0])?;
+	}
+	Ok(())
+}
+
+/**
+ * Changes the protection of the whole mapping at the address with the size.
+ *
+ * # Safety
+ * Nothing may still reference the range in a way, which the new
+ * protection forbids, like writing through a reference to it.
+ */
+pub unsafe fn mprotect(address: *mut u8, size: usize, protection: u64) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::MPROTECT, [address as u64, size as u64, protection, 0, 0, 0])?;
+	}
+	Ok(())
+}
+
+/*
+ * Creates shared memory with the size and returns its descriptor.
+ * Other processes open it by the name, unless it´s empty.
+ */
+pub fn shm_create(size: usize, name: &str) -> Result<u64> {
+	unsafe {
+		checked_syscall(syscall::SHM_CREATE, [size as u64, name.as_ptr() as u64, name.len() as u64, 0, 0, 0])
+	}
+}
+
+/*
+ * Opens the shared memory with the name with the flags (see file).
+ */
+pub fn shm_open(name: &str, flags: u64) -> Result<u64> {
+	unsafe {
+		checked_syscall(syscall::SHM_OPEN, [name.as_ptr() as u64, name.len() as u64, flags, 0, 0, 0])
+	}
+}
+
+/*
+ * Maps the shared memory of the descriptor at the address or
+ * anywhere, if it´s null. MAP_WRITE makes the mapping writable.
+ */
+pub fn shm_map(fd: u64, address: *mut u8, flags: u64) -> Result<*mut u8> {
+	let address = unsafe {
+		checked_syscall(syscall::SHM_MAP, [fd, address as u64, flags, 0, 0, 0])?
+	};
+	Ok(address as *mut u8)
+}
+
+/**
+ * Unmaps the shared memory mapped at the address.
+ *
+ * # Safety
+ * Nothing may still reference the mapping, it isn´t mapped afterwards.
+ */
+pub unsafe fn shm_unmap(address: *mut u8) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::SHM_UNMAP, [address as u64, 0, 0, 0, 0, 0])?;
+	}
+	Ok(())
+}
@@ -0,0 +1,53 @@
+use abi::syscall;
+use super::Result;
+use super::raw::{
+	syscall,
+	checked_syscall
+};
+
+pub use abi::process::*;
+
+/*
+ * Waits for the child with the pid or any child with ANY_CHILD.
+ * Returns the pid and the exit code of the child.
+ */
+pub fn wait(pid: u64) -> Result<(u64, u64)> {
+	let mut exit_code = 0;
+	let child = unsafe {
+		checked_syscall(syscall::WAIT, [pid, &mut exit_code as *mut u64 as u64, 0, 0, 0, 0])?
+	};
+	Ok((child, exit_code))
+}
+
+/*
+ * Returns the pid of the child to the parent and 0 to the child.
+ */
+pub fn fork() -> Result<u64> {
+	unsafe {
+		checked_syscall(syscall::FORK, [0; 6])
+	}
+}
+
+pub fn set_affinity(mask: u64) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::SET_AFFINITY, [mask, 0, 0, 0, 0, 0])?;
+	}
+	Ok(())
+}
+
+pub fn affinity() -> u64 {
+	unsafe {
+		syscall(syscall::AFFINITY, [0; 6])
+	}
+}
+
+/*
+ * Fills the records with the process table. Returns the amount
+ * of processes, which may exceed the amount of records.
+ */
+pub fn process_list(records: &mut [ProcessRecord]) -> Result<usize> {
+	let amount = unsafe {
+		checked_syscall(syscall::PROCESS_LIST, [records.as_mut_ptr() as u64, records.len() as u64, 0, 0, 0, 0])?
+	};
+	Ok(amount as usize)
+}
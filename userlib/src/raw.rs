@@ -0,0 +1,49 @@
+use core::arch::asm;
+use abi::error;
+use super::{
+	Error,
+	Result
+};
+
+/**
+ * Executes the syscall with the number and the arguments.
+ * Arguments, which the syscall doesn´t take, are ignored.
+ *
+ * # Safety
+ * The arguments have to be valid for the syscall (see abi::syscall).
+ * Pointers have to refer to memory of the size, the syscall reads or
+ * writes, and memory, the syscall frees or unmaps, mustn´t be used
+ * anymore.
+ */
+#[inline]
+pub unsafe fn syscall(number: u64, args: [u64; 6]) -> u64 {
+	let result;
+	unsafe {
+		asm!("syscall",
+			 in("rdi") number,
+			 in("rsi") args[0],
+			 in("rdx") args[1],
+			 in("r14") args[2],
+			 in("r8") args[3],
+			 in("r9") args[4],
+			 in("r15") args[5],
+			 lateout("rax") result,
+			 clobber_abi("sysv64"));
+	}
+	result
+}
+
+/**
+ * Executes a syscall and splits its return value into
+ * the result or the error.
+ *
+ * # Safety
+ * The same as for syscall.
+ */
+#[inline]
+pub unsafe fn checked_syscall(number: u64, args: [u64; 6]) -> Result<u64> {
+	let result = unsafe {
+		syscall(number, args)
+	};
+	error::decode(result).map_err(Error)
+}
@@ -0,0 +1,51 @@
+use abi::syscall;
+use super::Result;
+use super::raw::checked_syscall;
+use core::arch::naked_asm;
+
+pub use abi::signal::*;
+
+pub enum SignalHandler {
+	Default,
+	Ignore,
+	// Gets the signal as argument.
+	Handler(extern "sysv64" fn(u64))
+}
+
+/*
+ * Sends the signal to the process. Signal 0 only checks,
+ * whether the process exists.
+ */
+pub fn kill(pid: u64, signal: u64) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::KILL, [pid, signal, 0, 0, 0, 0])?;
+	}
+	Ok(())
+}
+
+/*
+ * Sets the handler of the signal. Returns the address of the
+ * previous handler or SIG_DFL or SIG_IGN.
+ */
+pub fn sigaction(signal: u64, handler: SignalHandler) -> Result<u64> {
+	let handler = match handler {
+		SignalHandler::Default => SIG_DFL,
+		SignalHandler::Ignore => SIG_IGN,
+		SignalHandler::Handler(handler) => handler as *const () as u64
+	};
+	unsafe {
+		checked_syscall(syscall::SIGACTION, [signal, handler, restorer as *const () as u64, 0, 0, 0])
+	}
+}
+
+/*
+ * Handlers return to it. The kernel finds the saved state right
+ * below the stack pointer, so it mustn´t be changed.
+ */
+#[unsafe(naked)]
+extern "sysv64" fn restorer() -> ! {
+	naked_asm!("mov rdi, {}",
+			   "syscall",
+			   "ud2",
+			   const syscall::SIGRETURN)
+}
@@ -0,0 +1,108 @@
+/*
+ * Reads the System V ABI stack, which programs start with. The
+ * entry point has to pass its initial stack pointer, before it
+ * changes the stack:
+ *
+ * #[unsafe(naked)]
+ * extern "sysv64" fn _start() -> ! {
+ *     naked_asm!("mov rdi, rsp", "call main")
+ * }
+ */
+use abi::process::AT_NULL;
+
+#[derive(Clone, Copy)]
+pub struct Startup {
+	stack_pointer: *const u64
+}
+
+impl Startup {
+	/// Reads the stack at the stack pointer.
+	///
+	/// # Safety
+	/// The pointer has to be the initial stack pointer of the program,
+	/// where the kernel built the System V stack, and the stack mustn´t
+	/// be overwritten, while the Startup is used.
+	pub unsafe fn from_stack(stack_pointer: *const u64) -> Startup {
+		Startup {
+			stack_pointer
+		}
+	}
+
+	pub fn argument_amount(&self) -> usize {
+		unsafe {
+			*self.stack_pointer as usize
+		}
+	}
+
+	pub fn arguments(&self) -> StringList {
+		StringList {
+			entry: unsafe {
+				self.stack_pointer.add(1)
+			}
+		}
+	}
+
+	/*
+	 * Environment variables in the form "NAME=value".
+	 */
+	pub fn environment(&self) -> StringList {
+		StringList {
+			entry: unsafe {
+				self.stack_pointer.add(self.argument_amount() + 2)
+			}
+		}
+	}
+
+	/*
+	 * Returns the value of the auxiliary vector entry with the type.
+	 */
+	pub fn auxiliary(&self, r#type: u64) -> Option<u64> {
+		let mut entry = self.environment().entry;
+		unsafe {
+			while *entry != 0x0 {
+				entry = entry.add(1);
+			}
+			entry = entry.add(1);
+			while *entry != AT_NULL {
+				if *entry == r#type {
+					return Some(*entry.add(1));
+				}
+				entry = entry.add(2);
+			}
+		}
+		None
+	}
+
+	/*
+	 * Returns the value of the environment variable with the name.
+	 */
+	pub fn variable(&self, name: &str) -> Option<&'static str> {
+		self.environment().find_map(|variable| {
+			variable.strip_prefix(name)?.strip_prefix('=')
+		})
+	}
+}
+
+/*
+ * Iterator over a NULL terminated array of pointers to NUL terminated
+ * strings. The kernel only passes UTF-8 strings.
+ */
+pub struct StringList {
+	entry: *const u64
+}
+
+impl Iterator for StringList {
+	type Item = &'static str;
+
+	fn next(&mut self) -> Option<&'static str> {
+		unsafe {
+			let address = *self.entry as *const u8;
+			if address.is_null() {
+				return None;
+			}
+			self.entry = self.entry.add(1);
+			let length = (0..).find(|idx| *address.add(*idx) == 0x0).unwrap();
+			Some(core::str::from_utf8_unchecked(core::slice::from_raw_parts(address, length)))
+		}
+	}
+}
@@ -0,0 +1,52 @@
+use abi::syscall;
+use super::Result;
+use super::raw::{
+	syscall,
+	checked_syscall
+};
+
+/*
+ * Entry point of a thread. It gets the argument of create_thread.
+ */
+pub type ThreadEntry = extern "sysv64" fn(u64) -> !;
+
+pub fn set_priority(priority: u64) -> Result<()> {
+	unsafe {
+		checked_syscall(syscall::SET_PRIORITY, [priority, 0, 0, 0, 0, 0])?;
+	}
+	Ok(())
+}
+
+pub fn priority() -> u64 {
+	unsafe {
+		syscall(syscall::PRIORITY, [0; 6])
+	}
+}
+
+/*
+ * Starts a thread with the stack top, which has to stay valid,
+ * until the thread exited. Returns the tid.
+ */
+pub fn create_thread(entry: ThreadEntry, stack_top: u64, argument: u64) -> Result<u64> {
+	unsafe {
+		checked_syscall(syscall::CREATE_THREAD, [entry as *const () as u64, stack_top, argument, 0, 0, 0])
+	}
+}
+
+pub fn exit_thread(exit_code: u64) -> ! {
+	unsafe {
+		syscall(syscall::EXIT_THREAD, [exit_code, 0, 0, 0, 0, 0]);
+	}
+	unreachable!()
+}
+
+/*
+ * Waits for the thread and returns its exit code.
+ */
+pub fn join_thread(tid: u64) -> Result<u64> {
+	let mut exit_code = 0;
+	unsafe {
+		checked_syscall(syscall::JOIN_THREAD, [tid, &mut exit_code as *mut u64 as u64, 0, 0, 0, 0])?;
+	}
+	Ok(exit_code)
+}
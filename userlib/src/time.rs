@@ -0,0 +1,34 @@
+use abi::syscall;
+use super::Result;
+use super::raw::{
+	syscall,
+	checked_syscall
+};
+
+pub use abi::clock::*;
+
+/*
+ * Blocks the current thread for at least the nanoseconds.
+ */
+pub fn sleep(ns: u64) {
+	unsafe {
+		syscall(syscall::SLEEP, [ns, 0, 0, 0, 0, 0]);
+	}
+}
+
+/*
+ * Returns the time of the clock in nanoseconds.
+ */
+pub fn clock_gettime(clock: u64) -> Result<u64> {
+	unsafe {
+		checked_syscall(syscall::CLOCK_GETTIME, [clock, 0, 0, 0, 0, 0])
+	}
+}
+
+pub fn clock_timespec(clock: u64) -> Result<Timespec> {
+	let mut timespec = Timespec::default();
+	unsafe {
+		checked_syscall(syscall::CLOCK_GETTIME, [clock, &mut timespec as *mut Timespec as u64, 0, 0, 0, 0])?;
+	}
+	Ok(timespec)
+}